@@ -0,0 +1,440 @@
+use std::io::Write;
+
+use crate::encode_unicode::encode_string;
+use crate::timing::{KeyState, key_events};
+
+/// How to space characters and words, set in [AudioOptions::timing]
+///
+/// The standard word `PARIS` lasts 50 units: 31 units for the characters themselves, 12 units for
+/// the four letter spaces, and 7 units for the word space. Learners often practice with characters
+/// sent at full speed, but with longer spaces to give them time to recognize each character.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Timing {
+    /// All spaces use the same unit as the characters
+    #[default]
+    Standard,
+    /// Letter and word spaces are stretched to give an overall speed of `effective_wpm`
+    Farnsworth {
+        /// Overall speed, in words per minute
+        effective_wpm: f64,
+    },
+    /// Only word spaces are stretched to give an overall speed of `effective_wpm`
+    Wordsworth {
+        /// Overall speed, in words per minute
+        effective_wpm: f64,
+    },
+}
+
+/// Shape of the edges of each element, set in [AudioOptions::envelope]
+///
+/// Switching the tone on and off instantly produces key clicks, which spread across the spectrum.
+/// Instead, the amplitude rises at the start of each element, and falls at its end, over
+/// [AudioOptions::rise_time].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Envelope {
+    /// Edges following half a period of a cosine
+    #[default]
+    RaisedCosine,
+    /// Edges following half a Blackman window, which are smoother
+    Blackman,
+}
+
+impl Envelope {
+    /// Gain at position `x` of a rising edge, between 0 and 1
+    fn gain(self, x: f64) -> f64 {
+        use std::f64::consts::PI;
+        match self {
+            Envelope::RaisedCosine => 0.5 - 0.5 * (PI * x).cos(),
+            Envelope::Blackman => 0.42 - 0.5 * (PI * x).cos() + 0.08 * (2.0 * PI * x).cos(),
+        }
+    }
+}
+
+/// Parameters of the audio generated by [morse_to_wav] and [text_to_wav]
+#[derive(Clone, Debug, PartialEq)]
+pub struct AudioOptions {
+    /// Speed of characters in words per minute, using the standard word `PARIS`, which lasts 50
+    /// dot units
+    pub wpm: f64,
+    /// Spacing of characters and words, to lower the overall speed below [wpm][Self::wpm]
+    pub timing: Timing,
+    /// Frequency of the tone, in hertz
+    pub frequency: f64,
+    /// Number of samples per second
+    pub sample_rate: u32,
+    /// Amplitude of the tone, between 0 and 1
+    pub amplitude: f64,
+    /// Shape of the edges of each element
+    pub envelope: Envelope,
+    /// Duration of the edges of each element, in seconds; use 0 for hard keying
+    pub rise_time: f64,
+}
+
+impl Default for AudioOptions {
+    fn default() -> Self {
+        AudioOptions {
+            wpm: 20.0,
+            timing: Timing::Standard,
+            frequency: 600.0,
+            sample_rate: 44100,
+            amplitude: 0.5,
+            envelope: Envelope::RaisedCosine,
+            rise_time: 0.005,
+        }
+    }
+}
+
+impl AudioOptions {
+    /// Check that the speeds, frequency and sample rate can be rendered
+    fn validate(&self) -> Result<(), std::io::Error> {
+        let invalid = |message| {
+            Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                message,
+            ))
+        };
+        if !(self.wpm.is_finite() && self.wpm > 0.0) {
+            return invalid("speed must be a positive number of words per minute");
+        }
+        if let Timing::Farnsworth { effective_wpm } | Timing::Wordsworth { effective_wpm } =
+            self.timing
+            && !(effective_wpm.is_finite() && effective_wpm > 0.0)
+        {
+            return invalid("effective speed must be a positive number of words per minute");
+        }
+        if !(self.frequency.is_finite() && self.frequency > 0.0) {
+            return invalid("frequency must be a positive number of hertz");
+        }
+        if self.sample_rate == 0 || self.sample_rate > u32::MAX / 2 {
+            return invalid("sample rate must be positive, and fit in a WAV file");
+        }
+        Ok(())
+    }
+}
+
+/// Number of samples of each key event
+struct EventSamples {
+    unit: usize,
+    letter_gap: usize,
+    word_gap: usize,
+}
+
+impl EventSamples {
+    fn new(options: &AudioOptions) -> Self {
+        let sample_rate = f64::from(options.sample_rate);
+        let unit = 1.2 / options.wpm;
+        let (letter_gap, word_gap) = match options.timing {
+            Timing::Standard => (3.0 * unit, 7.0 * unit),
+            Timing::Farnsworth { effective_wpm } => {
+                // the 19 units of spaces in PARIS take the time not used by the 31 units of
+                // characters
+                let gap_unit = (60.0 / effective_wpm - 31.0 * unit) / 19.0;
+                (3.0 * gap_unit, 7.0 * gap_unit)
+            }
+            Timing::Wordsworth { effective_wpm } => {
+                // the word space in PARIS takes the time not used by the rest
+                (3.0 * unit, 60.0 / effective_wpm - 43.0 * unit)
+            }
+        };
+        let samples = |seconds: f64| (seconds * sample_rate).round() as usize;
+        EventSamples {
+            unit: samples(unit),
+            // never shorten spaces when the effective speed is faster than the character speed
+            letter_gap: samples(letter_gap.max(3.0 * unit)),
+            word_gap: samples(word_gap.max(7.0 * unit)),
+        }
+    }
+
+    fn get(&self, state: KeyState, units: u32) -> usize {
+        match (state, units) {
+            (KeyState::Up, 3) => self.letter_gap,
+            (KeyState::Up | KeyState::Whitespace(_), units) if units % 7 == 0 => {
+                (units / 7) as usize * self.word_gap
+            }
+            (_, units) => units as usize * self.unit,
+        }
+    }
+}
+
+/// Write the header of a mono 16-bit PCM WAV file containing `samples` samples.
+fn write_wav_header(
+    output: &mut impl Write,
+    sample_rate: u32,
+    samples: usize,
+) -> Result<(), std::io::Error> {
+    let data_size = samples
+        .checked_mul(2)
+        .and_then(|size| u32::try_from(size).ok())
+        .filter(|size| *size <= u32::MAX - 36)
+        .ok_or_else(|| {
+            std::io::Error::new(std::io::ErrorKind::InvalidInput, "audio too long for WAV")
+        })?;
+    let mut header = Vec::with_capacity(44);
+    header.extend_from_slice(b"RIFF");
+    header.extend_from_slice(&(36 + data_size).to_le_bytes());
+    header.extend_from_slice(b"WAVE");
+    header.extend_from_slice(b"fmt ");
+    header.extend_from_slice(&16u32.to_le_bytes()); // size of the fmt chunk
+    header.extend_from_slice(&1u16.to_le_bytes()); // PCM
+    header.extend_from_slice(&1u16.to_le_bytes()); // mono
+    header.extend_from_slice(&sample_rate.to_le_bytes());
+    header.extend_from_slice(&(sample_rate * 2).to_le_bytes()); // bytes per second
+    header.extend_from_slice(&2u16.to_le_bytes()); // bytes per sample
+    header.extend_from_slice(&16u16.to_le_bytes()); // bits per sample
+    header.extend_from_slice(b"data");
+    header.extend_from_slice(&data_size.to_le_bytes());
+    output.write_all(&header)
+}
+
+/// Render Morse code as a mono 16-bit PCM WAV file into a [Write][std::io::Write] object.
+///
+/// Bytes from `morse` are converted into key events with [key_events][crate::key_events]: the
+/// tone is on while the key is down. The duration of a unit is set by [AudioOptions::wpm], and
+/// spaces can be stretched with [AudioOptions::timing].
+///
+/// An error of kind [InvalidInput][std::io::ErrorKind::InvalidInput] is returned when a speed, the
+/// frequency or the sample rate is not positive, or when the audio is too long for a WAV file.
+///
+/// ```
+/// let mut wav = Vec::new();
+/// let options = ripmors::AudioOptions::default();
+/// ripmors::morse_to_wav(b".--. .- .-. .. ... /", &mut wav, &options).unwrap();
+/// // PARIS lasts 50 units of 60 ms at 20 WPM, and each sample takes two bytes
+/// assert_eq!(wav.len(), 44 + 50 * 2646 * 2);
+/// ```
+pub fn morse_to_wav(
+    morse: &[u8],
+    output: &mut impl Write,
+    options: &AudioOptions,
+) -> Result<(), std::io::Error> {
+    options.validate()?;
+    let event_samples = EventSamples::new(options);
+    let samples = key_events(morse)
+        .try_fold(0usize, |samples, (state, units)| {
+            samples.checked_add(event_samples.get(state, units))
+        })
+        .ok_or_else(|| {
+            std::io::Error::new(std::io::ErrorKind::InvalidInput, "audio too long for WAV")
+        })?;
+    write_wav_header(output, options.sample_rate, samples)?;
+
+    let amplitude = options.amplitude.clamp(0.0, 1.0) * f64::from(i16::MAX);
+    let step = std::f64::consts::TAU * options.frequency / f64::from(options.sample_rate);
+    let rise_samples = (options.rise_time.max(0.0) * f64::from(options.sample_rate)) as usize;
+    let edge: Vec<f64> = (0..rise_samples)
+        .map(|i| {
+            options
+                .envelope
+                .gain((i as f64 + 0.5) / rise_samples as f64)
+        })
+        .collect();
+    let mut output_buf = Vec::with_capacity(1 << 15);
+    let mut t = 0;
+    for (state, units) in key_events(morse) {
+        let n = event_samples.get(state, units);
+        if state == KeyState::Down {
+            // the edges of short elements cannot overlap
+            let edge = &edge[..edge.len().min(n / 2)];
+            for i in 0..n {
+                let gain = edge.get(i).or_else(|| edge.get(n - 1 - i)).unwrap_or(&1.0);
+                let sample = amplitude * gain * (step * (t + i) as f64).sin();
+                output_buf.extend_from_slice(&(sample.round() as i16).to_le_bytes());
+            }
+        } else {
+            output_buf.resize(output_buf.len() + 2 * n, 0);
+        }
+        t += n;
+        if output_buf.len() >= 1 << 15 {
+            output.write_all(&output_buf)?;
+            output_buf.clear();
+        }
+    }
+    output.write_all(&output_buf)
+}
+
+/// Encode text into Morse code, and render it as a WAV file into a [Write][std::io::Write] object.
+///
+/// The text is encoded with [encode_string][crate::encode_string], then rendered with
+/// [morse_to_wav].
+pub fn text_to_wav(
+    input: &str,
+    output: &mut impl Write,
+    options: &AudioOptions,
+) -> Result<(), std::io::Error> {
+    morse_to_wav(encode_string(input).as_bytes(), output, options)
+}
+
+#[test]
+fn test_morse_to_wav() {
+    let options = AudioOptions {
+        wpm: 12.0,
+        timing: Timing::Standard,
+        frequency: 1000.0,
+        sample_rate: 8000,
+        amplitude: 1.0,
+        envelope: Envelope::RaisedCosine,
+        rise_time: 0.005,
+    };
+    let mut wav = Vec::new();
+    morse_to_wav(b".- /", &mut wav, &options).unwrap();
+    // one unit lasts 100 ms, so 800 samples; ".- /" lasts 1 + 1 + 3 + 7 units
+    let samples = 12 * 800;
+    assert_eq!(wav.len(), 44 + samples * 2);
+    assert_eq!(&wav[..4], b"RIFF");
+    assert_eq!(wav[4..8], (36 + samples as u32 * 2).to_le_bytes());
+    assert_eq!(&wav[8..16], b"WAVEfmt ");
+    assert_eq!(wav[24..28], 8000u32.to_le_bytes());
+    assert_eq!(&wav[36..40], b"data");
+    assert_eq!(wav[40..44], (samples as u32 * 2).to_le_bytes());
+
+    let samples: Vec<i16> = wav[44..]
+        .chunks_exact(2)
+        .map(|b| i16::from_le_bytes([b[0], b[1]]))
+        .collect();
+    // tone during the dot, peaking at full amplitude, then silence
+    assert_eq!(samples[..800].iter().max(), Some(&i16::MAX));
+    assert!(samples[800..1600].iter().all(|&s| s == 0));
+    assert_eq!(samples[1600..4000].iter().max(), Some(&i16::MAX));
+    assert!(samples[4000..].iter().all(|&s| s == 0));
+
+    let mut wav2 = Vec::new();
+    text_to_wav("a ", &mut wav2, &options).unwrap();
+    assert_eq!(wav, wav2);
+}
+
+#[test]
+fn test_invalid_options() {
+    let invalid = [
+        AudioOptions {
+            wpm: 0.0,
+            ..Default::default()
+        },
+        AudioOptions {
+            wpm: f64::NAN,
+            ..Default::default()
+        },
+        AudioOptions {
+            frequency: -600.0,
+            ..Default::default()
+        },
+        AudioOptions {
+            frequency: f64::INFINITY,
+            ..Default::default()
+        },
+        AudioOptions {
+            sample_rate: 0,
+            ..Default::default()
+        },
+        AudioOptions {
+            sample_rate: u32::MAX,
+            ..Default::default()
+        },
+        AudioOptions {
+            timing: Timing::Farnsworth { effective_wpm: 0.0 },
+            ..Default::default()
+        },
+        AudioOptions {
+            timing: Timing::Wordsworth {
+                effective_wpm: f64::NAN,
+            },
+            ..Default::default()
+        },
+        // the number of samples of a unit saturates to usize::MAX
+        AudioOptions {
+            wpm: 1e-300,
+            ..Default::default()
+        },
+    ];
+    for options in invalid {
+        let mut wav = Vec::new();
+        let error = morse_to_wav(b".- /", &mut wav, &options).unwrap_err();
+        assert_eq!(
+            error.kind(),
+            std::io::ErrorKind::InvalidInput,
+            "{options:?}"
+        );
+        assert!(wav.is_empty());
+    }
+}
+
+#[test]
+fn test_timing() {
+    let samples = |timing| {
+        let options = AudioOptions {
+            wpm: 20.0,
+            timing,
+            sample_rate: 8000,
+            ..Default::default()
+        };
+        let mut wav = Vec::new();
+        text_to_wav("PARIS ", &mut wav, &options).unwrap();
+        (wav.len() - 44) / 2
+    };
+    // a unit lasts 60 ms, or 480 samples
+    assert_eq!(samples(Timing::Standard), 50 * 480);
+    // 31 units of characters, then 4 letter spaces and 1 word space stretched to fill 6 seconds
+    // with 19 spaces units of 4.14 s / 19
+    let farnsworth = Timing::Farnsworth {
+        effective_wpm: 10.0,
+    };
+    assert_eq!(samples(farnsworth), 31 * 480 + 4 * 5229 + 12202);
+    // 43 units of characters and letter spaces, then a word space of 3.42 s
+    let wordsworth = Timing::Wordsworth {
+        effective_wpm: 10.0,
+    };
+    assert_eq!(samples(wordsworth), 43 * 480 + 27360);
+    assert_eq!(samples(wordsworth), 6 * 8000);
+    // effective speed cannot be faster than character speed
+    let faster = Timing::Farnsworth {
+        effective_wpm: 30.0,
+    };
+    assert_eq!(samples(faster), 50 * 480);
+}
+
+#[test]
+fn test_envelope() {
+    // fraction of the energy of a rendered dot that lies more than 500 Hz away from the tone
+    let spread = |envelope, rise_time| {
+        let options = AudioOptions {
+            wpm: 20.0,
+            frequency: 1000.0,
+            sample_rate: 8000,
+            envelope,
+            rise_time,
+            ..Default::default()
+        };
+        let mut wav = Vec::new();
+        morse_to_wav(b".", &mut wav, &options).unwrap();
+        let samples: Vec<f64> = wav[44..]
+            .chunks_exact(2)
+            .map(|b| f64::from(i16::from_le_bytes([b[0], b[1]])))
+            .collect();
+        let mut near = 0.0;
+        let mut far = 0.0;
+        for frequency in (0..4000u32).step_by(5) {
+            let step = std::f64::consts::TAU * f64::from(frequency) / 8000.0;
+            let (re, im) = samples
+                .iter()
+                .enumerate()
+                .fold((0.0, 0.0), |(re, im), (i, s)| {
+                    (
+                        re + s * (step * i as f64).cos(),
+                        im + s * (step * i as f64).sin(),
+                    )
+                });
+            let power = re * re + im * im;
+            if frequency.abs_diff(1000) <= 500 {
+                near += power;
+            } else {
+                far += power;
+            }
+        }
+        far / (near + far)
+    };
+    let hard = spread(Envelope::RaisedCosine, 0.0);
+    let raised_cosine = spread(Envelope::RaisedCosine, 0.005);
+    let blackman = spread(Envelope::Blackman, 0.005);
+    assert!(hard > 1e-3, "{hard}");
+    assert!(raised_cosine < 1e-5, "{raised_cosine}");
+    assert!(blackman < 1e-5, "{blackman}");
+}
@@ -0,0 +1,272 @@
+use std::io::Read;
+
+use crate::decode::{MorseAlphabet, decode_string};
+
+fn invalid_data(message: &str) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, message)
+}
+
+/// Read the sample rate and the samples of a mono 16-bit PCM WAV file.
+fn read_wav(input: &mut impl Read) -> Result<(u32, Vec<i16>), std::io::Error> {
+    let mut wav = Vec::new();
+    input.read_to_end(&mut wav)?;
+    if wav.len() < 12 || &wav[..4] != b"RIFF" || &wav[8..12] != b"WAVE" {
+        return Err(invalid_data("not a WAV file"));
+    }
+    let mut sample_rate = None;
+    let mut chunks = &wav[12..];
+    while chunks.len() >= 8 {
+        let id = &chunks[..4];
+        let size = u32::from_le_bytes(chunks[4..8].try_into().unwrap()) as usize;
+        let data = &chunks[8..];
+        let data = &data[..size.min(data.len())];
+        match id {
+            b"fmt " => {
+                if data.len() < 16 {
+                    return Err(invalid_data("truncated WAV format"));
+                }
+                let format = u16::from_le_bytes([data[0], data[1]]);
+                let channels = u16::from_le_bytes([data[2], data[3]]);
+                let bits_per_sample = u16::from_le_bytes([data[14], data[15]]);
+                if format != 1 || channels != 1 || bits_per_sample != 16 {
+                    return Err(invalid_data("only mono 16-bit PCM WAV is supported"));
+                }
+                sample_rate = Some(u32::from_le_bytes(data[4..8].try_into().unwrap()));
+            }
+            b"data" => {
+                let sample_rate = sample_rate.ok_or_else(|| invalid_data("missing WAV format"))?;
+                let samples = data
+                    .chunks_exact(2)
+                    .map(|b| i16::from_le_bytes([b[0], b[1]]))
+                    .collect();
+                return Ok((sample_rate, samples));
+            }
+            _ => (),
+        }
+        // chunks are padded to an even size
+        chunks = &chunks[(8 + size + size % 2).min(chunks.len())..];
+    }
+    Err(invalid_data("missing WAV data"))
+}
+
+/// Magnitude of the component of `samples` at `frequency`, using the Goertzel algorithm
+fn goertzel(samples: &[i16], frequency: f64, sample_rate: u32) -> f64 {
+    let coeff = 2.0 * (std::f64::consts::TAU * frequency / f64::from(sample_rate)).cos();
+    let (s1, s2) = samples.iter().fold((0.0, 0.0), |(s1, s2), &x| {
+        (f64::from(x) + coeff * s1 - s2, s1)
+    });
+    (s1 * s1 + s2 * s2 - coeff * s1 * s2).max(0.0).sqrt()
+}
+
+/// Find the frequency of the tone, by looking at the loudest blocks
+fn detect_frequency(blocks: &[&[i16]], sample_rate: u32) -> f64 {
+    let energy = |block: &[i16]| block.iter().map(|&x| f64::from(x).powi(2)).sum::<f64>();
+    let mut loudest: Vec<(f64, &[i16])> = blocks.iter().map(|b| (energy(b), *b)).collect();
+    loudest.sort_by(|a, b| b.0.total_cmp(&a.0));
+    loudest.truncate(64);
+    let max_frequency = (f64::from(sample_rate) / 2.0).min(4000.0) as u32;
+    (100..max_frequency)
+        .step_by(10)
+        .map(f64::from)
+        .map(|frequency| {
+            let magnitude = loudest
+                .iter()
+                .map(|(_, block)| goertzel(block, frequency, sample_rate))
+                .sum::<f64>();
+            (magnitude, frequency)
+        })
+        .max_by(|a, b| a.0.total_cmp(&b.0))
+        .map_or(600.0, |(_, frequency)| frequency)
+}
+
+/// Split samples into alternating runs of tone and silence, as numbers of blocks, starting with
+/// tone
+fn segment(samples: &[i16], sample_rate: u32) -> Vec<usize> {
+    // blocks of 5 ms, which is a tenth of a dot at 24 WPM
+    let block_size = (sample_rate as usize / 200).max(1);
+    let blocks: Vec<&[i16]> = samples.chunks(block_size).collect();
+    let frequency = detect_frequency(&blocks, sample_rate);
+    let magnitudes: Vec<f64> = blocks
+        .iter()
+        .map(|block| goertzel(block, frequency, sample_rate))
+        .collect();
+
+    // threshold halfway between the noise floor and the tone
+    let mut sorted = magnitudes.clone();
+    sorted.sort_by(f64::total_cmp);
+    let Some(&signal) = sorted.last() else {
+        return Vec::new();
+    };
+    let noise = sorted[sorted.len() / 10];
+    let threshold = (noise + signal) / 2.0;
+
+    let key_down: Vec<bool> = magnitudes.iter().map(|&m| m > threshold).collect();
+    // ignore leading and trailing silence
+    let (Some(first), Some(last)) = (
+        key_down.iter().position(|&k| k),
+        key_down.iter().rposition(|&k| k),
+    ) else {
+        return Vec::new();
+    };
+    key_down[first..=last]
+        .chunk_by(|a, b| a == b)
+        .map(|run| run.len())
+        .collect()
+}
+
+/// Convert alternating durations of tone and silence, starting with tone, into Morse code
+fn runs_to_morse(runs: &[usize]) -> String {
+    let marks: Vec<f64> = runs.iter().step_by(2).map(|&d| d as f64).collect();
+    let gaps: Vec<f64> = runs.iter().skip(1).step_by(2).map(|&d| d as f64).collect();
+    let min = |values: &[f64]| values.iter().copied().reduce(f64::min);
+    let max = |values: &[f64]| values.iter().copied().reduce(f64::max);
+    let mean = |values: &[f64]| values.iter().sum::<f64>() / values.len() as f64;
+    let (Some(shortest_mark), Some(longest_mark)) = (min(&marks), max(&marks)) else {
+        return String::new();
+    };
+
+    // estimate the duration of a dot
+    let unit = if longest_mark >= 2.0 * shortest_mark {
+        // both dots and dashes
+        let threshold = (shortest_mark + longest_mark) / 2.0;
+        let dots: Vec<f64> = marks.iter().copied().filter(|&d| d < threshold).collect();
+        mean(&dots)
+    } else if min(&gaps).is_some_and(|gap| shortest_mark >= 2.0 * gap) {
+        // only dashes, since the gaps between elements are shorter
+        mean(&marks) / 3.0
+    } else {
+        // only dots
+        mean(&marks)
+    };
+
+    // letter spaces might be stretched (Farnsworth timing), so compare word spaces to them
+    let spaces: Vec<f64> = gaps.iter().copied().filter(|&d| d >= 2.0 * unit).collect();
+    let word_threshold = match (min(&spaces), max(&spaces)) {
+        (Some(shortest), Some(longest)) if longest >= 1.5 * shortest => shortest * 5.0 / 3.0,
+        (Some(shortest), _) if shortest >= 5.0 * unit => shortest / 2.0,
+        _ => 5.0 * unit,
+    };
+
+    let mut morse = String::new();
+    for (i, &duration) in runs.iter().enumerate() {
+        let duration = duration as f64;
+        if i % 2 == 0 {
+            morse.push(if duration < 2.0 * unit { '.' } else { '-' });
+        } else if duration >= word_threshold {
+            morse.push_str(" / ");
+        } else if duration >= 2.0 * unit {
+            morse.push(' ');
+        }
+    }
+    morse
+}
+
+/// Recover Morse code from a WAV recording of a Morse transmission.
+///
+/// `input` must be a mono 16-bit PCM WAV file. The frequency of the tone is detected
+/// automatically, and its magnitude is tracked in blocks of 5 ms with the Goertzel algorithm.
+/// The duration of a dot is then estimated from the durations of tones and silences.
+///
+/// The output uses the same representation as [encode_string][crate::encode_string].
+///
+/// ```
+/// let mut wav = Vec::new();
+/// ripmors::text_to_wav("Morse code", &mut wav, &Default::default()).unwrap();
+/// let morse = ripmors::wav_to_morse(&mut wav.as_slice()).unwrap();
+/// assert_eq!(morse, "-- --- .-. ... . / -.-. --- -.. .");
+/// ```
+pub fn wav_to_morse(input: &mut impl Read) -> Result<String, std::io::Error> {
+    let (sample_rate, samples) = read_wav(input)?;
+    Ok(runs_to_morse(&segment(&samples, sample_rate)))
+}
+
+/// Decode a WAV recording of a Morse transmission into a [String].
+///
+/// The Morse code is recovered with [wav_to_morse], then decoded with
+/// [decode_string][crate::decode_string], using `alphabet`.
+///
+/// ```
+/// let mut wav = Vec::new();
+/// ripmors::text_to_wav("Привет", &mut wav, &Default::default()).unwrap();
+/// let text = ripmors::decode_wav(&mut wav.as_slice(), ripmors::to_russian).unwrap();
+/// assert_eq!(text, "ПРИВЕТ");
+/// ```
+pub fn decode_wav(
+    input: &mut impl Read,
+    alphabet: impl MorseAlphabet,
+) -> Result<String, std::io::Error> {
+    let morse = wav_to_morse(input)?;
+    Ok(decode_string(morse.as_bytes(), alphabet))
+}
+
+#[test]
+fn test_read_wav() {
+    let mut wav = Vec::new();
+    crate::morse_to_wav(b".", &mut wav, &Default::default()).unwrap();
+    let (sample_rate, samples) = read_wav(&mut wav.as_slice()).unwrap();
+    assert_eq!(sample_rate, 44100);
+    assert_eq!(samples.len(), 2646);
+
+    assert!(read_wav(&mut &b"RIFF\0\0\0\0WAVE"[..]).is_err());
+    assert!(read_wav(&mut &b"not a WAV file"[..]).is_err());
+    wav[22] = 2; // stereo
+    assert!(read_wav(&mut wav.as_slice()).is_err());
+}
+
+#[test]
+fn test_runs_to_morse() {
+    assert_eq!(runs_to_morse(&[]), "");
+    assert_eq!(runs_to_morse(&[10]), ".");
+    assert_eq!(runs_to_morse(&[10, 10, 30, 30, 10]), ".- .");
+    assert_eq!(runs_to_morse(&[10, 10, 10, 70, 10]), ".. / .");
+    assert_eq!(runs_to_morse(&[30, 10, 30, 30, 30]), "-- -");
+    // uneven durations
+    assert_eq!(runs_to_morse(&[8, 12, 33, 27, 11, 65, 28]), ".- . / -");
+    // stretched letter spaces
+    assert_eq!(runs_to_morse(&[10, 110, 10, 250, 10]), ". . / .");
+    // long spaces are word spaces when they cannot be compared to letter spaces
+    assert_eq!(runs_to_morse(&[10, 110, 10, 110, 10]), ". / . / .");
+}
+
+#[test]
+fn test_decode_wav() {
+    use crate::{AudioOptions, Timing, text_to_wav, to_standard};
+    let round_trip = |text, options: &AudioOptions| {
+        let mut wav = Vec::new();
+        text_to_wav(text, &mut wav, options).unwrap();
+        decode_wav(&mut wav.as_slice(), to_standard).unwrap()
+    };
+    let text = "THE QUICK BROWN FOX JUMPS OVER THE LAZY DOG";
+    assert_eq!(round_trip(text, &Default::default()), text);
+    let options = AudioOptions {
+        wpm: 35.0,
+        frequency: 1234.0,
+        sample_rate: 8000,
+        amplitude: 0.1,
+        ..Default::default()
+    };
+    assert_eq!(round_trip(text, &options), text);
+    let options = AudioOptions {
+        wpm: 15.0,
+        timing: Timing::Farnsworth { effective_wpm: 5.0 },
+        sample_rate: 8000,
+        ..Default::default()
+    };
+    assert_eq!(round_trip(text, &options), text);
+
+    // add noise
+    let mut wav = Vec::new();
+    let options = AudioOptions {
+        sample_rate: 8000,
+        ..Default::default()
+    };
+    text_to_wav(text, &mut wav, &options).unwrap();
+    let mut state = 1u32;
+    for sample in wav[44..].chunks_exact_mut(2) {
+        state = state.wrapping_mul(1664525).wrapping_add(1013904223);
+        let noise = (state >> 16) as i16 / 8;
+        let noisy = i16::from_le_bytes([sample[0], sample[1]]).saturating_add(noise);
+        sample.copy_from_slice(&noisy.to_le_bytes());
+    }
+    assert_eq!(decode_wav(&mut wav.as_slice(), to_standard).unwrap(), text);
+}
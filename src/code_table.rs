@@ -0,0 +1,493 @@
+use std::collections::HashMap;
+use std::io::{Read, Write};
+
+use crate::decode::MorseAlphabet;
+
+/// Error returned when building a [CodeTable].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CodeTableError {
+    /// Line of the code table where the error occurred, starting from 1, when parsing a code
+    /// table with [CodeTable::parse]
+    pub line: Option<usize>,
+    /// What went wrong
+    pub kind: CodeTableErrorKind,
+}
+
+/// Kind of [CodeTableError].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum CodeTableErrorKind {
+    /// The sequence is empty, has more than 63 elements, or contains something else than dots
+    /// (`.`) and dashes (`-`)
+    InvalidSequence(String),
+    /// The text mapped to a sequence is empty
+    EmptyText(String),
+    /// The sequence is already mapped to another text
+    Conflict {
+        sequence: String,
+        previous: String,
+        text: String,
+    },
+    /// The line is not of the form `sequence<TAB>text`
+    MissingText,
+}
+
+impl std::fmt::Display for CodeTableError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(line) = self.line {
+            write!(f, "line {line}: ")?;
+        }
+        match &self.kind {
+            CodeTableErrorKind::InvalidSequence(sequence) => {
+                write!(f, "invalid Morse sequence {sequence:?}")
+            }
+            CodeTableErrorKind::EmptyText(sequence) => {
+                write!(f, "empty text for Morse sequence {sequence:?}")
+            }
+            CodeTableErrorKind::Conflict {
+                sequence,
+                previous,
+                text,
+            } => write!(
+                f,
+                "conflict for Morse sequence {sequence:?} between {previous:?} and {text:?}"
+            ),
+            CodeTableErrorKind::MissingText => write!(f, "expected a Morse sequence and a text"),
+        }
+    }
+}
+
+impl std::error::Error for CodeTableError {}
+
+impl From<CodeTableError> for std::io::Error {
+    fn from(error: CodeTableError) -> Self {
+        std::io::Error::new(std::io::ErrorKind::InvalidData, error)
+    }
+}
+
+/// Convert a sequence of dots and dashes to the representation used by [MorseAlphabet]
+fn sequence_to_binary(sequence: &str) -> Option<u64> {
+    if sequence.is_empty() || sequence.len() >= 64 {
+        return None;
+    }
+    let mut binary = 1;
+    for c in sequence.bytes().rev() {
+        binary = match c {
+            b'.' => binary * 2,
+            b'-' => binary * 2 + 1,
+            _ => return None,
+        };
+    }
+    Some(binary)
+}
+
+#[test]
+fn test_sequence_to_binary() {
+    assert_eq!(sequence_to_binary("."), Some(0b10));
+    assert_eq!(sequence_to_binary("..-."), Some(0b10100));
+    assert_eq!(sequence_to_binary("........"), Some(0b100000000));
+    assert_eq!(sequence_to_binary(&"-".repeat(63)), Some(u64::MAX));
+    assert_eq!(sequence_to_binary(&"-".repeat(64)), None);
+    assert_eq!(sequence_to_binary(""), None);
+    assert_eq!(sequence_to_binary(".-x"), None);
+    assert_eq!(sequence_to_binary(".- ."), None);
+}
+
+/// Code book built at runtime, usable for both decoding and encoding.
+///
+/// A code table maps sequences of Morse elements to arbitrary text, such as letters,
+/// abbreviations or prosigns. It can be built from `(sequence, text)` pairs with
+/// [insert][CodeTable::insert], or parsed from a text file with [parse][CodeTable::parse]. Like the
+/// built-in variants, a sequence cannot be mapped to two different texts.
+///
+/// For decoding, a reference to a code table can be passed to [decode_string][crate::decode_string]
+/// or [decode_stream][crate::decode_stream]. For encoding, use
+/// [encode_string][CodeTable::encode_string] or [encode_stream][CodeTable::encode_stream].
+///
+/// ```
+/// let mut table = ripmors::CodeTable::from_alphabet(ripmors::to_standard);
+/// table.insert("-.-.--.-", "CQ").unwrap();
+/// table.insert("...-.-", "<SK>").unwrap();
+/// assert!(table.insert(".-.-.", "<AR>").is_err()); // already mapped to +
+///
+/// let morse = table.encode_string("CQ de F4HVV <sk>");
+/// assert_eq!(morse, "-.-.--.- / -.. . / ..-. ....- .... ...- ...- / ...-.-");
+/// assert_eq!(ripmors::decode_string(morse.as_bytes(), &table), "CQ DE F4HVV <SK>");
+/// ```
+#[derive(Clone, Debug)]
+pub struct CodeTable {
+    /// Texts of sequences of up to 7 elements, indexed like [MorseAlphabet::decode]
+    short: Vec<String>,
+    /// Texts of sequences of 8 elements or more, indexed like [MorseAlphabet::decode_long]
+    long: HashMap<u64, String>,
+    /// Sequences of each text, including the letter space
+    sequences: HashMap<String, String>,
+    /// Length of the longest text, in characters
+    max_text_chars: usize,
+}
+
+impl Default for CodeTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CodeTable {
+    /// Create an empty code table.
+    pub fn new() -> Self {
+        CodeTable {
+            short: vec![String::new(); 256],
+            long: HashMap::new(),
+            sequences: HashMap::new(),
+            max_text_chars: 0,
+        }
+    }
+
+    /// Create a code table containing the sequences of up to 7 elements of `alphabet`.
+    ///
+    /// This is useful to extend a built-in variant, such as [to_standard][crate::to_standard].
+    pub fn from_alphabet(alphabet: impl MorseAlphabet) -> Self {
+        let mut table = Self::new();
+        for len in 1..8 {
+            for bits in 0..1u8 << len {
+                let binary = (1 << len) | bits;
+                let text = alphabet.decode(binary);
+                if !text.is_empty() {
+                    let sequence: String = (0..len)
+                        .map(|i| if bits & (1 << i) == 0 { '.' } else { '-' })
+                        .collect();
+                    table
+                        .insert(&sequence, text)
+                        .expect("sequences of an alphabet are unique");
+                }
+            }
+        }
+        table
+    }
+
+    /// Map a sequence of dots (`.`) and dashes (`-`) to `text`.
+    ///
+    /// When several sequences are mapped to the same text, the first one is used for encoding.
+    pub fn insert(&mut self, sequence: &str, text: &str) -> Result<(), CodeTableError> {
+        let error = |kind| CodeTableError { line: None, kind };
+        let Some(binary) = sequence_to_binary(sequence) else {
+            return Err(error(CodeTableErrorKind::InvalidSequence(sequence.into())));
+        };
+        if text.is_empty() {
+            return Err(error(CodeTableErrorKind::EmptyText(sequence.into())));
+        }
+        let slot = if binary < 256 {
+            &mut self.short[binary as usize]
+        } else {
+            self.long.entry(binary).or_default()
+        };
+        if !slot.is_empty() && slot != text {
+            return Err(error(CodeTableErrorKind::Conflict {
+                sequence: sequence.into(),
+                previous: slot.clone(),
+                text: text.into(),
+            }));
+        }
+        *slot = text.into();
+        self.sequences
+            .entry(text.into())
+            .or_insert_with(|| format!("{sequence} "));
+        self.max_text_chars = self.max_text_chars.max(text.chars().count());
+        Ok(())
+    }
+
+    /// Parse a code table from text.
+    ///
+    /// Each line contains a sequence of dots (`.`) and dashes (`-`), followed by a tabulation or
+    /// spaces, and the corresponding text. Empty lines and lines starting with `#` are ignored.
+    ///
+    /// ```
+    /// let table = ripmors::CodeTable::parse("# Q codes\n--.-.-..-..\tQRL?\n--.-.-..---\tQRM\n");
+    /// let table = table.unwrap();
+    /// assert_eq!(table.encode_string("QRL? QRM"), "--.-.-..-.. / --.-.-..---");
+    /// ```
+    pub fn parse(text: &str) -> Result<Self, CodeTableError> {
+        let mut table = Self::new();
+        for (i, line) in text.lines().enumerate() {
+            let line_number = i + 1;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((sequence, text)) = line.split_once(char::is_whitespace) else {
+                return Err(CodeTableError {
+                    line: Some(line_number),
+                    kind: CodeTableErrorKind::MissingText,
+                });
+            };
+            table
+                .insert(sequence, text.trim_start())
+                .map_err(|e| CodeTableError {
+                    line: Some(line_number),
+                    ..e
+                })?;
+        }
+        Ok(table)
+    }
+
+    /// Read a code table from a file, in the format described in [parse][CodeTable::parse].
+    pub fn load(path: impl AsRef<std::path::Path>) -> Result<Self, std::io::Error> {
+        Ok(Self::parse(&std::fs::read_to_string(path)?)?)
+    }
+
+    /// Return the sequence of `text`, followed by a letter space, trying its uppercase form when
+    /// `text` itself is not in the code table
+    fn sequence(&self, text: &str) -> Option<&String> {
+        self.sequences
+            .get(text)
+            .or_else(|| self.sequences.get(&text.to_uppercase()))
+    }
+
+    /// Encode `input` into `output_buf`; return the number of bytes of `input` that were consumed
+    ///
+    /// When `is_final` is false, the last characters are left unconsumed when they might be the
+    /// start of a longer text of the code table. The letter space after the last sequence is
+    /// kept.
+    fn encode_buffer(&self, input: &str, is_final: bool, output_buf: &mut String) -> usize {
+        let mut rest = input;
+        while let Some(c) = rest.chars().next() {
+            let ends: Vec<_> = rest
+                .char_indices()
+                .map(|(i, c)| i + c.len_utf8())
+                .take(self.max_text_chars)
+                .collect();
+            if !is_final && ends.len() < self.max_text_chars {
+                break;
+            }
+            let matched = ends
+                .into_iter()
+                .rev()
+                .find_map(|len| Some((len, self.sequence(&rest[..len])?)));
+            if let Some((len, sequence)) = matched {
+                output_buf.push_str(sequence);
+                rest = &rest[len..];
+                continue;
+            }
+            match c {
+                ' ' => output_buf.push_str("/ "),
+                '\t' | '\n' | '\r' => {
+                    if output_buf.ends_with(' ') {
+                        output_buf.pop();
+                    }
+                    output_buf.push(c);
+                }
+                _ => (),
+            }
+            rest = &rest[c.len_utf8()..];
+        }
+        input.len() - rest.len()
+    }
+
+    /// Encode a [string slice][&str] into a [String] using this code table.
+    ///
+    /// The output uses the same format as [encode_string][crate::encode_string]. At each position,
+    /// the longest text of the code table is encoded. Text that is not in the code table is
+    /// looked up in uppercase, so that letters are encoded in either case. Characters that are not
+    /// in the code table are ignored, except for space, tab, line feed and carriage return.
+    pub fn encode_string(&self, input: &str) -> String {
+        let mut output = String::new();
+        self.encode_buffer(input, true, &mut output);
+        if output.ends_with(' ') {
+            output.pop();
+        }
+        output
+    }
+
+    /// Encode Unicode characters from a [Read][std::io::Read] object into a
+    /// [Write][std::io::Write] object using this code table.
+    ///
+    /// See [encode_string][CodeTable::encode_string] for the interpretation of `input` and the
+    /// format of the output. Invalid UTF-8 sequences are ignored.
+    ///
+    /// **Note:** This will read data from `input` until exhaustion.
+    pub fn encode_stream(
+        &self,
+        input: &mut impl Read,
+        output: &mut impl Write,
+    ) -> Result<(), std::io::Error> {
+        let mut input_buf = vec![0u8; 1 << 15];
+        let mut bytes_available = 0;
+        let mut output_buf = String::new();
+        loop {
+            let bytes_read = input.read(&mut input_buf[bytes_available..])?;
+            let is_final = bytes_read == 0;
+            bytes_available += bytes_read;
+            let (decoded, invalid_len) =
+                match simdutf8::compat::from_utf8(&input_buf[..bytes_available]) {
+                    Ok(decoded) => (decoded, 0),
+                    Err(e) => {
+                        // SAFETY: we already checked that the string was valid UTF-8 up to
+                        // `e.valid_up_to()`
+                        let decoded = unsafe {
+                            core::str::from_utf8_unchecked(&input_buf[..e.valid_up_to()])
+                        };
+                        // an incomplete sequence at the end might be completed by the next read
+                        let invalid_len = e.error_len().unwrap_or(if is_final {
+                            bytes_available - e.valid_up_to()
+                        } else {
+                            0
+                        });
+                        (decoded, invalid_len)
+                    }
+                };
+            let is_complete = is_final || invalid_len != 0;
+            let mut bytes_encoded = self.encode_buffer(decoded, is_complete, &mut output_buf);
+            if bytes_encoded == decoded.len() {
+                bytes_encoded += invalid_len;
+            }
+            // the letter space is replaced when a tab, a line feed or a carriage return follows
+            let pending_space = !is_final && output_buf.ends_with(' ');
+            if output_buf.ends_with(' ') {
+                output_buf.pop();
+            }
+            output.write_all(output_buf.as_bytes())?;
+            output_buf.clear();
+            if pending_space {
+                output_buf.push(' ');
+            }
+            if is_final {
+                return Ok(());
+            }
+            input_buf.copy_within(bytes_encoded..bytes_available, 0);
+            bytes_available -= bytes_encoded;
+        }
+    }
+}
+
+impl MorseAlphabet for &CodeTable {
+    fn decode(&self, elements: u8) -> &str {
+        &self.short[elements as usize]
+    }
+
+    fn decode_long(&self, elements: u64) -> &str {
+        self.long.get(&elements).map_or("", |text| text)
+    }
+}
+
+#[test]
+fn test_code_table_insert() {
+    let mut table = CodeTable::new();
+    table.insert(".-", "A").unwrap();
+    table.insert(".-", "A").unwrap();
+    table.insert(".-.-.-.-.-", "LONG").unwrap();
+    table.insert("-.-.-", "A").unwrap();
+    assert_eq!(
+        table.insert(".-", "B").unwrap_err(),
+        CodeTableError {
+            line: None,
+            kind: CodeTableErrorKind::Conflict {
+                sequence: ".-".into(),
+                previous: "A".into(),
+                text: "B".into(),
+            },
+        },
+    );
+    assert_eq!(
+        table.insert(".-.-.-.-.-", "B").unwrap_err().kind,
+        CodeTableErrorKind::Conflict {
+            sequence: ".-.-.-.-.-".into(),
+            previous: "LONG".into(),
+            text: "B".into(),
+        },
+    );
+    assert_eq!(
+        table.insert("", "C").unwrap_err().kind,
+        CodeTableErrorKind::InvalidSequence("".into()),
+    );
+    assert_eq!(
+        table.insert("._", "C").unwrap_err().kind,
+        CodeTableErrorKind::InvalidSequence("._".into()),
+    );
+    assert_eq!(
+        table.insert("-", "").unwrap_err().kind,
+        CodeTableErrorKind::EmptyText("-".into()),
+    );
+
+    // the first sequence is used for encoding
+    assert_eq!(table.encode_string("A LONG"), ".- / .-.-.-.-.-");
+    assert_eq!(
+        crate::decode_string(b".- -.-.- .-.-.-.-.- -", &table),
+        "AALONG"
+    );
+}
+
+#[test]
+fn test_code_table_parse() {
+    let table = CodeTable::parse("# comment\n\n.-\tA\n-...  B \n....-.-.\tHA HA\n").unwrap();
+    assert_eq!(table.encode_string("AB\nHA HA"), ".- -...\n....-.-.");
+    assert_eq!(
+        crate::decode_string(b".- -... / ....-.-.", &table),
+        "AB HA HA"
+    );
+
+    assert_eq!(
+        CodeTable::parse(".-\tA\n-...\n").unwrap_err(),
+        CodeTableError {
+            line: Some(2),
+            kind: CodeTableErrorKind::MissingText,
+        },
+    );
+    assert_eq!(
+        CodeTable::parse(".-\tA\n\n.-\tB\n").unwrap_err(),
+        CodeTableError {
+            line: Some(3),
+            kind: CodeTableErrorKind::Conflict {
+                sequence: ".-".into(),
+                previous: "A".into(),
+                text: "B".into(),
+            },
+        },
+    );
+}
+
+#[test]
+fn test_code_table_from_alphabet() {
+    let table = CodeTable::from_alphabet(crate::to_standard);
+    let input = "HELLO, WORLD!\nÀ BIENTÔT";
+    assert_eq!(
+        table.encode_string("HELLO, WORLD!"),
+        crate::encode_string("HELLO, WORLD!")
+    );
+    let morse = crate::encode_string(input);
+    assert_eq!(
+        crate::decode_string(morse.as_bytes(), &table),
+        crate::decode_string(morse.as_bytes(), crate::to_standard),
+    );
+}
+
+#[test]
+fn test_code_table_case() {
+    let mut table = CodeTable::from_alphabet(crate::to_standard);
+    table.insert("-.-.--.-", "CQ").unwrap();
+    table.insert(".-.-.-.-", "Dx").unwrap();
+    assert_eq!(table.encode_string("cq Cq"), "-.-.--.- / -.-.--.-");
+    // the exact text takes precedence
+    assert_eq!(
+        table.encode_string("Dx dx DX"),
+        ".-.-.-.- / -.. -..- / -.. -..-"
+    );
+}
+
+#[test]
+fn test_code_table_encode_stream() {
+    let mut table = CodeTable::from_alphabet(crate::to_standard);
+    table.insert("-.-.--.-", "CQ").unwrap();
+    table.insert("...-.-", "<SK>").unwrap();
+    let input = "cq de F4HVV <SK>\n".repeat(10000);
+    let mut output = Vec::new();
+    table
+        .encode_stream(&mut input.as_bytes(), &mut output)
+        .unwrap();
+    assert_eq!(output, table.encode_string(&input).as_bytes());
+
+    // invalid UTF-8 is ignored
+    let mut output = Vec::new();
+    table
+        .encode_stream(&mut &b"E\xffT \xe9"[..], &mut output)
+        .unwrap();
+    assert_eq!(output, b". - /");
+}
@@ -1,5 +1,8 @@
 use std::io::{Read, Write};
 
+use crate::decode_mapping::PROSIGNS;
+use crate::format::{MorseFormat, NormalizedReader, normalization_tokens, normalize};
+
 #[inline(always)] // prefer inline to avoid reloading constants in registers
 fn morse_to_binary_fast(bytes: &[u8; 8], len: usize) -> u8 {
     // Interpret next 8 bytes as u64
@@ -94,55 +97,467 @@ fn test_morse_to_binary() {
     assert_eq!(morse_to_binary(b"..-.", 4), 0b10100);
 }
 
-fn decode_buffer(input: &[u8], char_decode: fn(u8) -> char, output_buf: &mut Vec<char>) -> usize {
+fn morse_to_binary_long(bytes: &[u8]) -> Option<u64> {
+    if bytes.len() >= 64 {
+        return None;
+    }
+    let mut ret = 1;
+    for byte in bytes.iter().rev() {
+        ret *= 2;
+        ret |= (byte & 1) as u64;
+    }
+    Some(ret)
+}
+
+#[test]
+fn test_morse_to_binary_long() {
+    assert_eq!(morse_to_binary_long(b""), Some(1));
+    assert_eq!(morse_to_binary_long(b"..-."), Some(0b10100));
+    assert_eq!(morse_to_binary_long(b"........"), Some(0b100000000));
+    assert_eq!(morse_to_binary_long(b"...-...-"), Some(0b110001000));
+    assert_eq!(morse_to_binary_long(&[b'-'; 63]), Some(u64::MAX));
+    assert_eq!(morse_to_binary_long(&[b'-'; 64]), None);
+}
+
+/// Mapping from Morse code to text, used to decode Morse code.
+///
+/// The local variants of Morse code provided by this crate, such as [to_standard][crate::to_standard],
+/// are implemented as functions returning static strings. A custom code book can be provided by
+/// implementing this trait.
+///
+/// Sequences of elements are represented by packing one bit per element (0 for dot, 1 for dash),
+/// starting from the least significant bit, followed by a leading bit to detect the number of
+/// elements. For instance, `-.` is represented as `0b101`.
+///
+/// ```
+/// use ripmors::MorseAlphabet;
+///
+/// struct QCodes;
+///
+/// impl MorseAlphabet for QCodes {
+///     fn decode(&self, elements: u8) -> &str {
+///         ripmors::to_standard(elements)
+///     }
+///     fn decode_long(&self, elements: u64) -> &str {
+///         match elements {
+///             // --.- .-. .-.. without letter spaces
+///             0b1001_0010_1011 => "QRL?",
+///             _ => "",
+///         }
+///     }
+/// }
+///
+/// let string = ripmors::decode_string(b"--.-.-..-.. / -.. .", QCodes);
+/// assert_eq!(string, "QRL? DE");
+/// ```
+pub trait MorseAlphabet {
+    /// Return the text corresponding to a sequence of up to 7 elements, or the empty string if
+    /// the sequence is unknown.
+    fn decode(&self, elements: u8) -> &str;
+
+    /// Return the text corresponding to a sequence of 8 to 63 elements, or the empty string if
+    /// the sequence is unknown.
+    ///
+    /// By default, no such sequence is known.
+    fn decode_long(&self, elements: u64) -> &str {
+        let _ = elements;
+        ""
+    }
+}
+
+impl<F: Fn(u8) -> &'static str> MorseAlphabet for F {
+    fn decode(&self, elements: u8) -> &str {
+        self(elements)
+    }
+}
+
+/// Position of an undecodable sequence, as reported by [decode_buffer] in strict mode
+type UnknownSequence = usize;
+
+/// Handle a sequence of elements that does not correspond to any character
+#[inline(always)]
+fn push_unknown<const STRICT: bool>(
+    chunk_start: usize,
+    options: &DecodeOptions,
+    output_buf: &mut Vec<u8>,
+) -> Result<(), UnknownSequence> {
+    if STRICT {
+        return Err(chunk_start);
+    }
+    if let Some(replacement) = options.replacement {
+        output_buf.extend_from_slice(replacement.encode_utf8(&mut [0; 4]).as_bytes());
+    }
+    Ok(())
+}
+
+/// Decode a sequence of more than 7 elements, return whether it was recognized
+#[cold]
+fn decode_long(
+    elements: &[u8],
+    alphabet: &impl MorseAlphabet,
+    options: &DecodeOptions,
+    output_buf: &mut Vec<u8>,
+) -> bool {
+    if let Some(binary) = morse_to_binary_long(elements) {
+        let decoded = alphabet.decode_long(binary);
+        if !decoded.is_empty() {
+            output_buf.extend_from_slice(decoded.as_bytes());
+            return true;
+        }
+    }
+    // The error signal is made of eight dots or more
+    if elements.iter().all(|c| c & 1 == 0) {
+        if options.error_signal == ErrorSignal::DeletePreviousWord {
+            output_buf.truncate(previous_word_start(output_buf));
+        }
+        true
+    } else {
+        false
+    }
+}
+
+/// Return where the last word of the current line starts in `output_buf`, including the spaces
+/// surrounding it
+fn previous_word_start(output_buf: &[u8]) -> usize {
+    let mut start = output_buf.len();
+    while start > 0 && output_buf[start - 1] == b' ' {
+        start -= 1;
+    }
+    while start > 0 && !matches!(output_buf[start - 1], b' ' | b'\t' | b'\n' | b'\r') {
+        start -= 1;
+    }
+    while start > 0 && output_buf[start - 1] == b' ' {
+        start -= 1;
+    }
+    start
+}
+
+#[test]
+fn test_previous_word_start() {
+    let f = |s: &str| previous_word_start(s.as_bytes());
+    assert_eq!(f(""), 0);
+    assert_eq!(f("HELLO"), 0);
+    assert_eq!(f("HELLO WORLD"), 5);
+    assert_eq!(f("HELLO WORLD  "), 5);
+    assert_eq!(f("HELLO\nWORLD "), 6);
+    assert_eq!(f("HELLO\n"), 6);
+    assert_eq!(f("ДА НЕТ"), 4);
+}
+
+/// Return the precomposed kana for `base` followed by `mark`, a dakuten (゛) or handakuten (゜)
+fn compose_kana(base: char, mark: char) -> Option<char> {
+    let c = base as u32;
+    // Hiragana are laid out like Katakana, 0x60 code points before
+    let offset = if ('ぁ'..='ゖ').contains(&base) {
+        0x60
+    } else {
+        0
+    };
+    let katakana = c + offset;
+    let composed = match (katakana, mark) {
+        // カ ガ キ ギ … チ ヂ
+        (0x30AB..=0x30C2, '゛') if (katakana - 0x30AB).is_multiple_of(2) => katakana + 1,
+        // ツ ヅ, テ デ, ト ド
+        (0x30C4 | 0x30C6 | 0x30C8, '゛') => katakana + 1,
+        // ハ バ パ … ホ ボ ポ
+        (0x30CF..=0x30DD, '゛') if (katakana - 0x30CF).is_multiple_of(3) => katakana + 1,
+        (0x30CF..=0x30DD, '゜') if (katakana - 0x30CF).is_multiple_of(3) => katakana + 2,
+        // ウ ヴ
+        (0x30A6, '゛') => 0x30F4,
+        // ワ ヷ, ヰ ヸ, ヱ ヹ, ヲ ヺ, only in Katakana
+        (0x30EF..=0x30F2, '゛') if offset == 0 => katakana + 8,
+        _ => return None,
+    };
+    char::from_u32(composed - offset)
+}
+
+/// Combine `decoded` with the kana at the end of `output_buf` when it is a dakuten (゛) or
+/// handakuten (゜), return whether they were combined
+fn compose_previous_kana(decoded: &str, output_buf: &mut Vec<u8>) -> bool {
+    let mark = match decoded {
+        "゛" => '゛',
+        "゜" => '゜',
+        _ => return false,
+    };
+    // kana are encoded on three bytes
+    let Some(start) = output_buf.len().checked_sub(3) else {
+        return false;
+    };
+    let Ok(previous) = std::str::from_utf8(&output_buf[start..]) else {
+        return false;
+    };
+    let mut chars = previous.chars();
+    let (Some(base), None) = (chars.next(), chars.next()) else {
+        return false;
+    };
+    let Some(composed) = compose_kana(base, mark) else {
+        return false;
+    };
+    output_buf.truncate(start);
+    output_buf.extend_from_slice(composed.encode_utf8(&mut [0; 4]).as_bytes());
+    true
+}
+
+#[test]
+fn test_compose_kana() {
+    let f = |s: &str| {
+        let mut chars = s.chars();
+        let (base, mark) = (chars.next().unwrap(), chars.next().unwrap());
+        compose_kana(base, mark)
+    };
+    let pairs = [
+        ("カ゛", "ガ"),
+        ("チ゛", "ヂ"),
+        ("ツ゛", "ヅ"),
+        ("ト゛", "ド"),
+        ("ハ゛", "バ"),
+        ("ハ゜", "パ"),
+        ("ホ゜", "ポ"),
+        ("ウ゛", "ヴ"),
+        ("ヲ゛", "ヺ"),
+        ("か゛", "が"),
+        ("そ゛", "ぞ"),
+        ("ふ゜", "ぷ"),
+        ("う゛", "ゔ"),
+    ];
+    for (input, composed) in pairs {
+        assert_eq!(f(input), composed.chars().next(), "{input}");
+    }
+    for input in ["カ゜", "ッ゛", "ナ゛", "ン゛", "ゐ゛", "A゛", "ー゛"] {
+        assert_eq!(f(input), None, "{input}");
+    }
+}
+
+/// Push the text corresponding to a sequence of up to 7 elements
+#[inline(always)]
+fn push_decoded<const STRICT: bool>(
+    binary: u8,
+    chunk_start: usize,
+    alphabet: &impl MorseAlphabet,
+    options: &DecodeOptions,
+    output_buf: &mut Vec<u8>,
+) -> Result<(), UnknownSequence> {
+    let decoded = alphabet.decode(binary);
+    // a prosign only replaces the character it shares its code with in the standard variant, so
+    // that other scripts are not altered
+    if options.prosigns
+        && !PROSIGNS[binary as usize].is_empty()
+        && (decoded.is_empty()
+            || decoded == crate::to_standard(binary)
+            || decoded == crate::to_standard_lowercase(binary))
+    {
+        output_buf.extend_from_slice(PROSIGNS[binary as usize].as_bytes());
+    } else if !decoded.is_empty() {
+        if !(options.compose_kana && compose_previous_kana(decoded, output_buf)) {
+            output_buf.extend_from_slice(decoded.as_bytes());
+        }
+    } else if binary != 1 {
+        // only non-empty sequences are unknown
+        push_unknown::<STRICT>(chunk_start, options, output_buf)?;
+    }
+    Ok(())
+}
+
+/// Decode Morse code from `input` into UTF-8 in `output_buf`, return how many bytes of `input`
+/// were consumed
+pub(crate) fn decode_buffer<const STRICT: bool>(
+    input: &[u8],
+    alphabet: &impl MorseAlphabet,
+    options: &DecodeOptions,
+    output_buf: &mut Vec<u8>,
+) -> Result<usize, UnknownSequence> {
     let mut chunk_start = 0;
     let last_seven_bytes = input.len().saturating_sub(7);
     for i in 0..last_seven_bytes {
         let c = input[i];
         if c <= b' ' {
-            // SAFETY: `chunk_start < i < input.len() - 7` so the first argument is always a valid
-            // pointer to eight initialized bytes
-            let eight_bytes: &[u8; 8] = unsafe { &*input.as_ptr().add(chunk_start).cast() };
-            let binary = morse_to_binary_fast(eight_bytes, i - chunk_start);
-            let decoded = char_decode(binary);
-            if decoded != '\0' {
-                output_buf.push(decoded);
+            let len = i - chunk_start;
+            if len > 7 {
+                if !decode_long(&input[chunk_start..i], alphabet, options, output_buf) {
+                    push_unknown::<STRICT>(chunk_start, options, output_buf)?;
+                }
+            } else {
+                // SAFETY: `chunk_start < i < input.len() - 7` so the first argument is always a
+                // valid pointer to eight initialized bytes
+                let eight_bytes: &[u8; 8] = unsafe { &*input.as_ptr().add(chunk_start).cast() };
+                let binary = morse_to_binary_fast(eight_bytes, len);
+                push_decoded::<STRICT>(binary, chunk_start, alphabet, options, output_buf)?;
             }
             chunk_start = i + 1;
             if c != b' ' {
-                output_buf.push(c as char);
+                output_buf.push(c);
             }
         } else if c == b'/' {
-            output_buf.push(' ');
+            output_buf.push(b' ');
             chunk_start = i + 1;
         }
     }
     for i in last_seven_bytes..input.len() {
         let c = input[i];
         if c <= b' ' {
-            let binary = morse_to_binary(&input[chunk_start..], i - chunk_start);
-            let decoded = char_decode(binary);
-            if decoded != '\0' {
-                output_buf.push(decoded);
-            }
+            decode_chunk::<STRICT>(input, chunk_start, i, alphabet, options, output_buf)?;
             chunk_start = i + 1;
             if c != b' ' {
-                output_buf.push(c as char);
+                output_buf.push(c);
             }
         } else if c == b'/' {
-            output_buf.push(' ');
+            output_buf.push(b' ');
             chunk_start = i + 1;
         }
     }
-    chunk_start
+    Ok(chunk_start)
 }
 
-fn decode_buffer_end(input: &[u8], char_decode: fn(u8) -> char, output_buf: &mut Vec<char>) {
-    let chunk_start = decode_buffer(input, char_decode, output_buf);
-    let binary = morse_to_binary(&input[chunk_start..], input.len() - chunk_start);
-    let decoded = char_decode(binary);
-    if decoded != '\0' {
-        output_buf.push(decoded);
+/// Decode the elements in `input[chunk_start..chunk_end]`, without assuming anything about the
+/// bytes that follow
+pub(crate) fn decode_chunk<const STRICT: bool>(
+    input: &[u8],
+    chunk_start: usize,
+    chunk_end: usize,
+    alphabet: &impl MorseAlphabet,
+    options: &DecodeOptions,
+    output_buf: &mut Vec<u8>,
+) -> Result<(), UnknownSequence> {
+    let len = chunk_end - chunk_start;
+    if len > 7 {
+        if !decode_long(
+            &input[chunk_start..chunk_end],
+            alphabet,
+            options,
+            output_buf,
+        ) {
+            push_unknown::<STRICT>(chunk_start, options, output_buf)?;
+        }
+    } else {
+        let binary = morse_to_binary(&input[chunk_start..], len);
+        push_decoded::<STRICT>(binary, chunk_start, alphabet, options, output_buf)?;
+    }
+    Ok(())
+}
+
+pub(crate) fn decode_buffer_end<const STRICT: bool>(
+    input: &[u8],
+    alphabet: &impl MorseAlphabet,
+    options: &DecodeOptions,
+    output_buf: &mut Vec<u8>,
+) -> Result<(), UnknownSequence> {
+    let chunk_start = decode_buffer::<STRICT>(input, alphabet, options, output_buf)?;
+    decode_chunk::<STRICT>(
+        input,
+        chunk_start,
+        input.len(),
+        alphabet,
+        options,
+        output_buf,
+    )
+}
+
+/// Return how many bytes of `output_buf` will not be changed by decoding more input: everything,
+/// except for the current line if it might still be edited by the error signal, and for the last
+/// kana if it might still be combined with a dakuten or handakuten
+pub(crate) fn final_len(output_buf: &[u8], options: &DecodeOptions) -> usize {
+    let len = match options.error_signal {
+        ErrorSignal::Ignore => output_buf.len(),
+        ErrorSignal::DeletePreviousWord => output_buf
+            .iter()
+            .rposition(|&c| c == b'\n')
+            .map_or(0, |i| i + 1),
+    };
+    if options.compose_kana
+        && let Some(start) = len.checked_sub(3)
+        && let Ok(previous) = std::str::from_utf8(&output_buf[start..len])
+        && previous
+            .chars()
+            .next()
+            .is_some_and(|base| compose_kana(base, '゛').is_some())
+    {
+        start
+    } else {
+        len
+    }
+}
+
+/// Convert the output of [decode_buffer] into a [String]
+fn into_string(output_buf: Vec<u8>) -> String {
+    // SAFETY: `decode_buffer` only outputs string slices, and ASCII characters
+    unsafe { String::from_utf8_unchecked(output_buf) }
+}
+
+/// Error returned by [decode_string_strict] and [decode_stream_strict] when the input contains a
+/// sequence of Morse elements that does not correspond to any character.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DecodeError {
+    /// Offset of the first byte of the sequence in the input, starting from 0
+    pub offset: usize,
+    /// Line of the sequence in the input, starting from 1
+    pub line: usize,
+    /// Column of the first byte of the sequence in its line, in bytes, starting from 1
+    pub column: usize,
+    /// Elements of the unknown sequence, as they appeared in the input
+    pub sequence: String,
+}
+
+impl DecodeError {
+    fn new(input: &[u8], sequence_start: usize, position: Position) -> Self {
+        let sequence_end = input[sequence_start..]
+            .iter()
+            .position(|&c| c <= b' ' || c == b'/')
+            .map_or(input.len(), |len| sequence_start + len);
+        let mut position = position;
+        position.advance(&input[..sequence_start]);
+        DecodeError {
+            offset: position.offset,
+            line: position.line,
+            column: position.column,
+            sequence: String::from_utf8_lossy(&input[sequence_start..sequence_end]).into_owned(),
+        }
+    }
+}
+
+impl std::fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "unknown Morse sequence {:?} at line {}, column {}",
+            self.sequence, self.line, self.column,
+        )
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+impl From<DecodeError> for std::io::Error {
+    fn from(error: DecodeError) -> Self {
+        std::io::Error::new(std::io::ErrorKind::InvalidData, error)
+    }
+}
+
+/// Position in the input, used to locate errors in strict mode
+#[derive(Clone, Copy)]
+struct Position {
+    offset: usize,
+    line: usize,
+    column: usize,
+}
+
+impl Position {
+    fn new() -> Self {
+        Position {
+            offset: 0,
+            line: 1,
+            column: 1,
+        }
+    }
+
+    fn advance(&mut self, bytes: &[u8]) {
+        self.offset += bytes.len();
+        match bytes.iter().rposition(|&c| c == b'\n') {
+            Some(last_newline) => {
+                self.line += bytes.iter().filter(|&&c| c == b'\n').count();
+                self.column = bytes.len() - last_newline;
+            }
+            None => self.column += bytes.len(),
+        }
     }
 }
 
@@ -156,27 +571,37 @@ fn decode_buffer_end(input: &[u8], char_decode: fn(u8) -> char, output_buf: &mut
 /// - Slash (/) is interpreted as word space;
 /// - Tab (\t), line feed (\n) and carriage return (\r) are kept as-is.
 ///
-/// Other ASCII characters, and non-ASCII bytes, such as UTF-8 encodings, are ignored.
+/// Other ASCII characters, and non-ASCII bytes, such as UTF-8 encodings, are ignored. With
+/// [normalize_symbols][DecodeOptions::normalize_symbols] (see [decode_string_with]), common
+/// alternate symbols are also recognized:
 ///
-/// The second argument selects a local variant of Morse code. It should be one of:
+/// - Underscore (_), en dash (–), em dash (—) and minus sign (−) are interpreted as Morse dash;
+/// - Middle dot (·) and bullet (•) are interpreted as Morse dot.
+///
+/// The second argument selects a local variant of Morse code. It should be a custom
+/// [MorseAlphabet], or one of:
 ///
 /// - [to_standard][crate::to_standard] for [International Morse code and Latin extensions](https://en.wikipedia.org/wiki/Morse_code#Letters,_numbers,_punctuation,_prosigns_for_Morse_code_and_non-Latin_variants);
 /// - [to_arabic][crate::to_arabic] for Arabic;
 /// - [to_greek][crate::to_greek] for Greek;
 /// - [to_hebrew][crate::to_hebrew] for Hebrew;
 /// - [to_japanese][crate::to_japanese] for [Japanese](https://en.wikipedia.org/wiki/Wabun_code) (Katakana);
+/// - [to_japanese_hiragana][crate::to_japanese_hiragana] for [Japanese](https://en.wikipedia.org/wiki/Wabun_code) (Hiragana);
 /// - [to_korean][crate::to_korean] for [Korean](https://en.wikipedia.org/wiki/SKATS) (Hangul);
 /// - [to_russian][crate::to_russian] for [Russian](https://en.wikipedia.org/wiki/Russian_Morse_code) (Cyrillic).
 ///
+/// Latin, Greek and Cyrillic letters are decoded in uppercase. To decode them in lowercase, use
+/// [to_standard_lowercase][crate::to_standard_lowercase],
+/// [to_greek_lowercase][crate::to_greek_lowercase] or
+/// [to_russian_lowercase][crate::to_russian_lowercase] instead.
+///
 /// ```
 /// let morse = "-- --- .-. ... . / -.-. --- -.. .";
 /// let string = ripmors::decode_string(morse.as_bytes(), ripmors::to_standard);
 /// assert_eq!(string, "MORSE CODE");
 /// ```
-pub fn decode_string(input: &[u8], char_decode: fn(u8) -> char) -> String {
-    let mut output_buf = Vec::with_capacity(input.len());
-    decode_buffer_end(input, char_decode, &mut output_buf);
-    output_buf.iter().collect()
+pub fn decode_string(input: &[u8], alphabet: impl MorseAlphabet) -> String {
+    decode_string_with(input, alphabet, &DecodeOptions::default())
 }
 
 /// Decode Morse code from a [Read][std::io::Read] object into a [Write][std::io::Write] object.
@@ -189,20 +614,32 @@ pub fn decode_string(input: &[u8], char_decode: fn(u8) -> char) -> String {
 /// - Slash (/) is interpreted as word space;
 /// - Tab (\t), line feed (\n) and carriage return (\r) are kept as-is.
 ///
-/// Other ASCII characters, and non-ASCII bytes, such as UTF-8 encodings, are ignored.
+/// Other ASCII characters, and non-ASCII bytes, such as UTF-8 encodings, are ignored. With
+/// [normalize_symbols][DecodeOptions::normalize_symbols] (see [decode_stream_with]), common
+/// alternate symbols are also recognized:
+///
+/// - Underscore (_), en dash (–), em dash (—) and minus sign (−) are interpreted as Morse dash;
+/// - Middle dot (·) and bullet (•) are interpreted as Morse dot.
 ///
 /// **Note:** This will read data from `input` until exhaustion.
 ///
-/// The third argument selects a local variant of Morse code. It should be one of:
+/// The third argument selects a local variant of Morse code. It should be a custom
+/// [MorseAlphabet], or one of:
 ///
 /// - [to_standard][crate::to_standard] for [International Morse code and Latin extensions](https://en.wikipedia.org/wiki/Morse_code#Letters,_numbers,_punctuation,_prosigns_for_Morse_code_and_non-Latin_variants);
 /// - [to_arabic][crate::to_arabic] for Arabic;
 /// - [to_greek][crate::to_greek] for Greek;
 /// - [to_hebrew][crate::to_hebrew] for Hebrew;
 /// - [to_japanese][crate::to_japanese] for [Japanese](https://en.wikipedia.org/wiki/Wabun_code) (Katakana);
+/// - [to_japanese_hiragana][crate::to_japanese_hiragana] for [Japanese](https://en.wikipedia.org/wiki/Wabun_code) (Hiragana);
 /// - [to_korean][crate::to_korean] for [Korean](https://en.wikipedia.org/wiki/SKATS) (Hangul);
 /// - [to_russian][crate::to_russian] for [Russian](https://en.wikipedia.org/wiki/Russian_Morse_code) (Cyrillic).
 ///
+/// Latin, Greek and Cyrillic letters are decoded in uppercase. To decode them in lowercase, use
+/// [to_standard_lowercase][crate::to_standard_lowercase],
+/// [to_greek_lowercase][crate::to_greek_lowercase] or
+/// [to_russian_lowercase][crate::to_russian_lowercase] instead.
+///
 /// For example, the program below decodes international Morse code.
 /// ```no_run
 /// # #[allow(clippy::needless_doctest_main)]
@@ -215,11 +652,239 @@ pub fn decode_string(input: &[u8], char_decode: fn(u8) -> char) -> String {
 pub fn decode_stream(
     input: &mut impl Read,
     output: &mut impl Write,
-    char_decode: fn(u8) -> char,
+    alphabet: impl MorseAlphabet,
+) -> Result<(), std::io::Error> {
+    decode_stream_with(input, output, alphabet, &DecodeOptions::default())
+}
+
+/// Options controlling how Morse code is decoded by [decode_string_with] and
+/// [decode_stream_with].
+///
+/// The default options give the same behavior as [decode_string] and [decode_stream].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct DecodeOptions {
+    /// Character emitted in place of sequences of elements that do not correspond to any
+    /// character in the selected variant, such as `'\u{FFFD}'`, `'#'` or `'*'`. When `None`,
+    /// such sequences are dropped.
+    pub replacement: Option<char>,
+    /// What to do when receiving the error signal (eight dots or more)
+    pub error_signal: ErrorSignal,
+    /// Render prosigns in angle-bracket notation, such as `<AR>` for `.-.-.` instead of `+`
+    ///
+    /// A prosign only replaces the character of the selected variant when it is the character of
+    /// [to_standard][crate::to_standard] for the same code, such as `+` for `<AR>`, or when there
+    /// is none, so that the letters of other scripts are kept.
+    pub prosigns: bool,
+    /// Tokens used to write the Morse code
+    pub format: MorseFormat,
+    /// Also accept common alternate symbols, as found in Morse code pasted from websites and chat:
+    /// underscore (_), en dash (–), em dash (—) and minus sign (−) for dashes, and middle dot (·)
+    /// and bullet (•) for dots
+    pub normalize_symbols: bool,
+    /// Also interpret a vertical bar (|) as a word space
+    pub bar_word_gap: bool,
+    /// Interpret a run of three spaces or more as a word space, instead of several letter spaces
+    pub spaces_word_gap: bool,
+    /// Combine a kana followed by a dakuten (゛) or handakuten (゜) into a single precomposed
+    /// character, such as `ゴ` for `コ゛`, as with [to_japanese][crate::to_japanese] and
+    /// [to_japanese_hiragana][crate::to_japanese_hiragana]
+    ///
+    /// Marks that cannot be combined with the kana before them are kept as-is.
+    pub compose_kana: bool,
+}
+
+impl DecodeOptions {
+    /// Whether the input must be converted to the default tokens before decoding
+    pub(crate) fn needs_normalization(&self) -> bool {
+        self.normalize_symbols
+            || self.bar_word_gap
+            || self.spaces_word_gap
+            || !self.format.is_default()
+    }
+
+    /// Tokens to pass to [normalize][DecodeOptions::normalize], see [normalization_tokens]
+    pub(crate) fn normalization_tokens(&self) -> Vec<(Vec<u8>, u8)> {
+        normalization_tokens(self)
+    }
+
+    /// Convert Morse code from `input` to the default tokens, see [normalize]
+    pub(crate) fn normalize(
+        &self,
+        tokens: &[(Vec<u8>, u8)],
+        input: &[u8],
+        is_final: bool,
+        output_buf: &mut Vec<u8>,
+    ) -> usize {
+        normalize(self, tokens, input, is_final, output_buf)
+    }
+}
+
+/// Behavior of the decoder when receiving the error signal (`........`), sent by operators to
+/// cancel the word they are transmitting.
+///
+/// Runs of more than eight dots are also interpreted as the error signal.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum ErrorSignal {
+    /// Drop the error signal, leaving the output unchanged
+    #[default]
+    Ignore,
+    /// Delete the last word of the current line, along with the spaces around it
+    ///
+    /// **Note:** Since words cannot be deleted once written, [decode_stream_with] holds back the
+    /// current line until a line feed (`\n`) is decoded, or the end of `input` is reached.
+    DeletePreviousWord,
+}
+
+/// Decode Morse code from a [byte slice][slice] into a [String], with custom [DecodeOptions].
+///
+/// See [decode_string] for the interpretation of `input` and `alphabet`.
+///
+/// ```
+/// let options = ripmors::DecodeOptions {
+///     replacement: Some('#'),
+///     ..Default::default()
+/// };
+/// let morse = b"-- --- .-.-- ... . / -.-. --- -.. .";
+/// let string = ripmors::decode_string_with(morse, ripmors::to_standard, &options);
+/// assert_eq!(string, "MO#SE CODE");
+/// ```
+pub fn decode_string_with(
+    input: &[u8],
+    alphabet: impl MorseAlphabet,
+    options: &DecodeOptions,
+) -> String {
+    let mut output_buf = Vec::with_capacity(input.len());
+    if !options.needs_normalization() {
+        decode_buffer_end::<false>(input, &alphabet, options, &mut output_buf)
+    } else {
+        let mut normalized = Vec::with_capacity(input.len());
+        let tokens = options.normalization_tokens();
+        options.normalize(&tokens, input, true, &mut normalized);
+        decode_buffer_end::<false>(&normalized, &alphabet, options, &mut output_buf)
+    }
+    .expect("only strict decoding reports unknown sequences");
+    into_string(output_buf)
+}
+
+/// Decode Morse code from a [Read][std::io::Read] object into a [Write][std::io::Write] object,
+/// with custom [DecodeOptions].
+///
+/// See [decode_stream] for the interpretation of `input` and `alphabet`.
+///
+/// **Note:** This will read data from `input` until exhaustion.
+pub fn decode_stream_with(
+    input: &mut impl Read,
+    output: &mut impl Write,
+    alphabet: impl MorseAlphabet,
+    options: &DecodeOptions,
+) -> Result<(), std::io::Error> {
+    if !options.needs_normalization() {
+        decode_stream_normalized(input, output, &alphabet, options)
+    } else {
+        let mut input = NormalizedReader::new(input, options);
+        decode_stream_normalized(&mut input, output, &alphabet, options)
+    }
+}
+
+/// Decode Morse code written with the default tokens, see [decode_stream_with]
+fn decode_stream_normalized(
+    input: &mut impl Read,
+    output: &mut impl Write,
+    alphabet: &impl MorseAlphabet,
+    options: &DecodeOptions,
+) -> Result<(), std::io::Error> {
+    let mut input_buf = vec![0u8; 1 << 15];
+    let mut bytes_available = 0;
+    let mut output_buf = Vec::with_capacity(1 << 15);
+    loop {
+        let bytes_read = input.read(&mut input_buf[bytes_available..])?;
+        if bytes_read == 0 {
+            break;
+        }
+        bytes_available += bytes_read;
+
+        let input = &input_buf[..bytes_available];
+        let bytes_used = decode_buffer::<false>(input, alphabet, options, &mut output_buf)
+            .expect("only strict decoding reports unknown sequences");
+
+        // flush buffer, except for what might still be edited, see `final_len`
+        let bytes_final = final_len(&output_buf, options);
+        if bytes_final != 0 {
+            output.write_all(&output_buf[..bytes_final])?;
+            output_buf.drain(..bytes_final);
+        }
+
+        input_buf.copy_within(bytes_used..bytes_available, 0);
+        bytes_available -= bytes_used;
+    }
+
+    if bytes_available != 0 {
+        let input = &input_buf[..bytes_available];
+        decode_buffer_end::<false>(input, alphabet, options, &mut output_buf)
+            .expect("only strict decoding reports unknown sequences");
+    }
+    if !output_buf.is_empty() {
+        output.write_all(&output_buf)?;
+    }
+
+    Ok(())
+}
+
+/// Decode Morse code from a [byte slice][slice] into a [String], rejecting unknown sequences.
+///
+/// This behaves like [decode_string], except that sequences of elements that do not correspond to
+/// any character in the selected variant are reported as a [DecodeError] instead of being
+/// silently dropped.
+///
+/// ```
+/// let string = ripmors::decode_string_strict(b"-- --- .-. ... .", ripmors::to_standard);
+/// assert_eq!(string.unwrap(), "MORSE");
+///
+/// let error = ripmors::decode_string_strict(b"... --- ...\n.-.-.-.-", ripmors::to_standard);
+/// let error = error.unwrap_err();
+/// assert_eq!(error.offset, 12);
+/// assert_eq!((error.line, error.column), (2, 1));
+/// assert_eq!(error.sequence, ".-.-.-.-");
+/// ```
+pub fn decode_string_strict(
+    input: &[u8],
+    alphabet: impl MorseAlphabet,
+) -> Result<String, DecodeError> {
+    let mut output_buf = Vec::with_capacity(input.len());
+    match decode_buffer_end::<true>(input, &alphabet, &DecodeOptions::default(), &mut output_buf) {
+        Ok(()) => Ok(into_string(output_buf)),
+        Err(sequence_start) => Err(DecodeError::new(input, sequence_start, Position::new())),
+    }
+}
+
+/// Decode Morse code from a [Read][std::io::Read] object into a [Write][std::io::Write] object,
+/// rejecting unknown sequences.
+///
+/// This behaves like [decode_stream], except that decoding stops at the first sequence of
+/// elements that does not correspond to any character in the selected variant. Everything
+/// decoded before that sequence is written to `output`, and an error of kind
+/// [InvalidData][std::io::ErrorKind::InvalidData] wrapping a [DecodeError] is returned.
+///
+/// ```
+/// let mut input: &[u8] = b"... --- ... / .-.--";
+/// let mut output = Vec::new();
+/// let error = ripmors::decode_stream_strict(&mut input, &mut output, ripmors::to_standard);
+/// assert_eq!(output, b"SOS ");
+///
+/// let error = error.unwrap_err();
+/// let error: &ripmors::DecodeError = error.get_ref().unwrap().downcast_ref().unwrap();
+/// assert_eq!(error.offset, 14);
+/// assert_eq!(error.sequence, ".-.--");
+/// ```
+pub fn decode_stream_strict(
+    input: &mut impl Read,
+    output: &mut impl Write,
+    alphabet: impl MorseAlphabet,
 ) -> Result<(), std::io::Error> {
     let mut input_buf = vec![0u8; 1 << 15];
     let mut bytes_available = 0;
     let mut output_buf = Vec::with_capacity(1 << 15);
+    let mut position = Position::new();
     loop {
         let bytes_read = input.read(&mut input_buf[bytes_available..])?;
         if bytes_read == 0 {
@@ -227,24 +892,41 @@ pub fn decode_stream(
         }
         bytes_available += bytes_read;
 
-        let bytes_used = decode_buffer(&input_buf[..bytes_available], char_decode, &mut output_buf);
+        let result = decode_buffer::<true>(
+            &input_buf[..bytes_available],
+            &alphabet,
+            &DecodeOptions::default(),
+            &mut output_buf,
+        );
 
         // flush buffer
         if !output_buf.is_empty() {
-            let decoded: String = output_buf.iter().collect();
-            output.write_all(decoded.as_bytes())?;
+            output.write_all(&output_buf)?;
             output_buf.clear();
         }
 
+        let bytes_used = match result {
+            Ok(bytes_used) => bytes_used,
+            Err(sequence_start) => {
+                let input = &input_buf[..bytes_available];
+                return Err(DecodeError::new(input, sequence_start, position).into());
+            }
+        };
+
+        position.advance(&input_buf[..bytes_used]);
         input_buf.copy_within(bytes_used..bytes_available, 0);
         bytes_available -= bytes_used;
     }
 
     if bytes_available != 0 {
-        decode_buffer_end(&input_buf[..bytes_available], char_decode, &mut output_buf);
+        let input = &input_buf[..bytes_available];
+        let result =
+            decode_buffer_end::<true>(input, &alphabet, &DecodeOptions::default(), &mut output_buf);
         if !output_buf.is_empty() {
-            let decoded: String = output_buf.iter().collect();
-            output.write_all(decoded.as_bytes())?;
+            output.write_all(&output_buf)?;
+        }
+        if let Err(sequence_start) = result {
+            return Err(DecodeError::new(input, sequence_start, position).into());
         }
     }
 
@@ -261,3 +943,282 @@ fn test_standard_decode() {
         "HELLO, WORLD!",
     );
 }
+
+#[test]
+fn test_strict_decode() {
+    use crate::decode_mapping::to_standard;
+    let f = |s| decode_string_strict(s, to_standard);
+    assert_eq!(f(b".--. .- .-. .. ...").unwrap(), "PARIS");
+    assert_eq!(f(b"... ---  ...\n/ ..\t").unwrap(), "SOS\n I\t");
+    assert_eq!(
+        f(b"... --- ...\n.. ..--.- ..-- --- .-.-.-.- -").unwrap_err(),
+        DecodeError {
+            offset: 31,
+            line: 2,
+            column: 20,
+            sequence: ".-.-.-.-".to_string(),
+        },
+    );
+    // unknown sequence at the end of the input
+    assert_eq!(
+        f(b"... .-.--").unwrap_err(),
+        DecodeError {
+            offset: 4,
+            line: 1,
+            column: 5,
+            sequence: ".-.--".to_string(),
+        },
+    );
+}
+
+#[test]
+fn test_strict_decode_stream() {
+    use crate::decode_mapping::to_standard;
+
+    // place the error across the boundary of the internal buffer
+    let mut input = ".-.-.-\n".repeat(5000).into_bytes();
+    input.extend_from_slice(b"..--.- --..-.--..-. .-.-.-");
+    let mut output = Vec::new();
+    let error = decode_stream_strict(&mut input.as_slice(), &mut output, to_standard).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+    let error: &DecodeError = error.get_ref().unwrap().downcast_ref().unwrap();
+    assert_eq!(
+        error,
+        &DecodeError {
+            offset: 35007,
+            line: 5001,
+            column: 8,
+            sequence: "--..-.--..-.".to_string(),
+        },
+    );
+    assert_eq!(output, format!("{}_", ".\n".repeat(5000)).as_bytes());
+}
+
+#[test]
+fn test_decode_replacement() {
+    use crate::decode_mapping::to_standard;
+    let options = DecodeOptions {
+        replacement: Some('\u{FFFD}'),
+        ..Default::default()
+    };
+    let f = |s| decode_string_with(s, to_standard, &options);
+    assert_eq!(f(b".--. .- .-. .. ..."), "PARIS");
+    // empty sequences are not replaced
+    assert_eq!(f(b"...  --- / ...\n"), "SO S\n");
+    assert_eq!(f(b"... ...... ... .-.--"), "S\u{FFFD}S\u{FFFD}");
+
+    // same result in stream
+    let input = "...... / ..-- \t.-.--\n".repeat(5000);
+    let mut output = Vec::new();
+    decode_stream_with(&mut input.as_bytes(), &mut output, to_standard, &options).unwrap();
+    assert_eq!(output, f(input.as_bytes()).as_bytes());
+}
+
+#[test]
+fn test_decode_long() {
+    use crate::decode_mapping::{to_russian, to_standard};
+    let f = |s| decode_string(s, to_standard);
+    // long sequences do not alias to shorter ones
+    assert_eq!(f(b"... ...-...-..-.-.-- ..."), "SS");
+    assert_eq!(f(b"... ...-...-..-.-.--"), "S");
+    // the error signal is ignored by default
+    assert_eq!(f(b"... ........ ..."), "SS");
+    assert_eq!(f(b"... ............ ..."), "SS");
+
+    // long sequences are reported in strict mode, but not the error signal
+    let error = decode_string_strict(b"... ...-...-..-.-.-- ...", to_standard).unwrap_err();
+    assert_eq!(error.sequence, "...-...-..-.-.--");
+    assert_eq!(error.offset, 4);
+    assert_eq!(decode_string_strict(b"........", to_standard).unwrap(), "");
+
+    // delete previous word
+    let options = DecodeOptions {
+        error_signal: ErrorSignal::DeletePreviousWord,
+        ..Default::default()
+    };
+    let f = |s| decode_string_with(s, to_standard, &options);
+    assert_eq!(
+        f(b"... --- ... / .-- --- ........ / .-- --- .-. -.."),
+        "SOS WORD"
+    );
+    assert_eq!(
+        f(b"... --- ... / .-- --- / ........ / .-- --- .-. -.."),
+        "SOS WORD"
+    );
+    assert_eq!(f(b"... --- ...\n........ -.-"), "SOS\nK");
+    assert_eq!(f(b"... / ........ ........"), "");
+    let g = |s| decode_string_with(s, to_russian, &options);
+    assert_eq!(g(b"-.. .- / -. . ........ / -. . -"), "ДА НЕТ");
+
+    // same result in stream
+    let input = "... --- ... / .-- --- ........ / .-- --- .-. -.. / ........\n".repeat(5000);
+    let mut output = Vec::new();
+    decode_stream_with(&mut input.as_bytes(), &mut output, to_standard, &options).unwrap();
+    assert_eq!(output, f(input.as_bytes()).as_bytes());
+}
+
+#[test]
+fn test_decode_prosigns() {
+    use crate::decode_mapping::to_standard;
+    let f = |s| decode_string(s, to_standard);
+    assert_eq!(f(b".-.-. -...- ...-.-"), "+=");
+    let options = DecodeOptions {
+        prosigns: true,
+        ..Default::default()
+    };
+    let f = |s| decode_string_with(s, to_standard, &options);
+    assert_eq!(f(b".-.-. -...- ...-.-"), "<AR><BT><SK>");
+    assert_eq!(f(b"-.-. --.- / -...- / -.-"), "CQ <BT> K");
+    assert_eq!(f(b"-.--. ..-.- .-..."), "<KN><INT><AS>");
+    // round trip
+    let morse = crate::encode_string("CQ <KA> 73 <SK>");
+    assert_eq!(f(morse.as_bytes()), "CQ <KA> 73 <SK>");
+    let f = |s, alphabet: fn(u8) -> &'static str| decode_string_with(s, alphabet, &options);
+    assert_eq!(f(b"...-. .-.-.", crate::to_standard_lowercase), "<SN><AR>");
+
+    // letters of other scripts are kept
+    assert_eq!(f(b".-.-. -.-.-", crate::to_japanese), "ンサ");
+    assert_eq!(f(b".-.-. ...-.-", crate::to_japanese), "ン<SK>");
+    assert_eq!(f(b"-...- -.-.-", crate::to_russian), "-;");
+}
+
+#[test]
+fn test_decode_custom_alphabet() {
+    struct Abbreviations;
+    impl MorseAlphabet for Abbreviations {
+        fn decode(&self, elements: u8) -> &str {
+            match elements {
+                0b101 => "N",
+                0b1111 => "OK",
+                _ => "",
+            }
+        }
+        fn decode_long(&self, elements: u64) -> &str {
+            match elements {
+                0b1_1001_0110 => "THANKS",
+                _ => "",
+            }
+        }
+    }
+    assert_eq!(
+        decode_string(b"--- / -. / -- / .--.-..-", Abbreviations),
+        "OK N  THANKS"
+    );
+    let error = decode_string_strict(b"--- .", Abbreviations).unwrap_err();
+    assert_eq!(error.sequence, ".");
+
+    // closures work too
+    assert_eq!(
+        decode_string(b"--- ... .", |e| if e == 0b10 { "E" } else { "" }),
+        "E"
+    );
+}
+
+#[test]
+fn test_decode_format() {
+    let options = DecodeOptions {
+        format: MorseFormat {
+            dot: "di".into(),
+            dash: "dah".into(),
+            letter_separator: " ".into(),
+            word_separator: "/".into(),
+        },
+        ..Default::default()
+    };
+    let morse = b"dahdah dahdahdah didahdit dididit dit / dahdidahdit dahdahdah dahdidit dit";
+    let string = decode_string_with(morse, crate::to_standard, &options);
+    assert_eq!(string, "MORSE CODE");
+
+    // tokens split across reads
+    let morse = [&morse[..]; 1000].join(&b" / "[..]);
+    let mut output = Vec::new();
+    decode_stream_with(&mut &morse[..], &mut output, crate::to_standard, &options).unwrap();
+    assert_eq!(output, ["MORSE CODE"; 1000].join(" ").as_bytes());
+}
+
+#[test]
+fn test_decode_normalize_symbols() {
+    let options = DecodeOptions {
+        normalize_symbols: true,
+        ..Default::default()
+    };
+    let morse = "–– ——— ·−· ••• . / _._. ___ -.. ·".as_bytes();
+    let string = decode_string_with(morse, crate::to_standard, &options);
+    assert_eq!(string, "MORSE CODE");
+    let mut output = Vec::new();
+    decode_stream_with(&mut &morse[..], &mut output, crate::to_standard, &options).unwrap();
+    assert_eq!(output, b"MORSE CODE");
+
+    // unrelated input decodes like without normalization
+    for morse in [&b".-/-"[..], b"-- /.-/ / -.-./\n/..", b".- x -"] {
+        assert_eq!(
+            decode_string_with(morse, crate::to_standard, &options),
+            decode_string(morse, crate::to_standard),
+        );
+    }
+}
+
+#[test]
+fn test_decode_word_gaps() {
+    let options = DecodeOptions {
+        bar_word_gap: true,
+        spaces_word_gap: true,
+        ..Default::default()
+    };
+    let f = |morse: &[u8]| decode_string_with(morse, crate::to_standard, &options);
+    assert_eq!(f(b"-- --- .-. ... . | -.-. --- -.. ."), "MORSE CODE");
+    assert_eq!(f(b"-- --- .-. ... .|-.-. --- -.. ."), "MORSE CODE");
+    assert_eq!(f(b"-- --- .-. ... .   -.-. --- -.. ."), "MORSE CODE");
+    assert_eq!(f(b"-- --- .-. ... . / -.-. --- -.. .  "), "MORSE CODE");
+    assert_eq!(f(b"... --- ...   \n"), "SOS \n");
+
+    // runs of spaces longer than the buffer of decode_stream_with
+    let morse = format!("... {}--- ...", " ".repeat(40000));
+    assert_eq!(f(morse.as_bytes()), "S OS");
+    let mut output = Vec::new();
+    decode_stream_with(
+        &mut morse.as_bytes(),
+        &mut output,
+        crate::to_standard,
+        &options,
+    )
+    .unwrap();
+    assert_eq!(output, b"S OS");
+}
+
+#[test]
+fn test_decode_compose_kana() {
+    use crate::{Decoder, Variant, encode_string_with, to_japanese, to_japanese_hiragana};
+
+    let morse = encode_string_with("ごはん、ぱん ウ゛ア", Variant::Japanese);
+    let options = DecodeOptions {
+        compose_kana: true,
+        ..Default::default()
+    };
+    let f = |alphabet: fn(u8) -> &'static str, options: &DecodeOptions| {
+        decode_string_with(morse.as_bytes(), alphabet, options)
+    };
+    assert_eq!(
+        f(to_japanese, &Default::default()),
+        "コ゛ハン、ハ゜ン ウ゛ア"
+    );
+    assert_eq!(f(to_japanese, &options), "ゴハン、パン ヴア");
+    assert_eq!(f(to_japanese_hiragana, &options), "ごはん、ぱん ゔあ");
+    // marks after a word space are kept as-is
+    assert_eq!(
+        decode_string_with(b"---- / ..", to_japanese, &options),
+        "コ ゛"
+    );
+
+    // the last kana is held back until the next character
+    let mut decoder = Decoder::with_options(to_japanese_hiragana, options.clone());
+    assert_eq!(decoder.push(b"---- "), "");
+    assert_eq!(decoder.push(b".. "), "ご");
+    assert_eq!(decoder.push(b"-... "), "");
+    assert_eq!(decoder.finish(), "は");
+
+    let morse = format!("{morse} ").repeat(10000);
+    let mut output = Vec::new();
+    decode_stream_with(&mut morse.as_bytes(), &mut output, to_japanese, &options).unwrap();
+    assert_eq!(output, "ゴハン、パン ヴア".repeat(10000).as_bytes());
+}
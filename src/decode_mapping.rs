@@ -15,10 +15,70 @@ macro_rules! element_to_binary_digit {
     };
 }
 
+/// Convert a Katakana character, encoded as 3 bytes of UTF-8, into the corresponding Hiragana;
+/// other characters are returned unchanged
+const fn katakana_to_hiragana(text: &str) -> [u8; 3] {
+    let bytes = text.as_bytes();
+    if bytes.len() != 3 {
+        panic!("Expected a 3-byte character");
+    }
+    let mut c = ((bytes[0] as u32 & 0x0f) << 12)
+        | ((bytes[1] as u32 & 0x3f) << 6)
+        | (bytes[2] as u32 & 0x3f);
+    // ァ (U+30A1) to ヶ (U+30F6)
+    if c >= 0x30a1 && c <= 0x30f6 {
+        c -= 0x60;
+    }
+    [
+        0xe0 | (c >> 12) as u8,
+        0x80 | (c >> 6 & 0x3f) as u8,
+        0x80 | (c & 0x3f) as u8,
+    ]
+}
+
 macro_rules! to_script {
-    ($(#[$outer:meta])* $array_name:ident, $function_name:ident, $($elements:expr => $character:expr),+ $(,)? ) => {
-        const $array_name: [char; 256] = {
-            let mut x = ['\0'; 256];
+    (@hiragana $text:literal) => {{
+        static HIRAGANA: [u8; 3] = katakana_to_hiragana($text);
+        match core::str::from_utf8(&HIRAGANA) {
+            Ok(text) => text,
+            Err(_) => panic!("Invalid UTF-8"),
+        }
+    }};
+    // Japanese, with the Hiragana text derived from the Katakana text
+    (
+        $(#[$outer:meta])* $array_name:ident, $function_name:ident,
+        $(#[$hiragana_outer:meta])* hiragana $hiragana_array_name:ident, $hiragana_function_name:ident,
+        $($elements:expr => $text:literal),+ $(,)?
+    ) => {
+        to_script! {
+            $(#[$outer])* $array_name, $function_name,
+            $($elements => $text),+
+        }
+        to_script! {
+            $(#[$hiragana_outer])* $hiragana_array_name, $hiragana_function_name,
+            $($elements => to_script!(@hiragana $text)),+
+        }
+    };
+    (@lowercase $text:literal) => { $text };
+    (@lowercase $text:literal, $lowercase:literal) => { $lowercase };
+    // Scripts with case, with the lowercase text after a vertical bar when it differs
+    (
+        $(#[$outer:meta])* $array_name:ident, $function_name:ident,
+        $(#[$lowercase_outer:meta])* lowercase $lowercase_array_name:ident, $lowercase_function_name:ident,
+        $($elements:expr => $text:literal $(| $lowercase:literal)?),+ $(,)?
+    ) => {
+        to_script! {
+            $(#[$outer])* $array_name, $function_name,
+            $($elements => $text),+
+        }
+        to_script! {
+            $(#[$lowercase_outer])* $lowercase_array_name, $lowercase_function_name,
+            $($elements => to_script!(@lowercase $text $(, $lowercase)?)),+
+        }
+    };
+    ($(#[$outer:meta])* $array_name:ident, $function_name:ident, $($elements:expr => $text:expr),+ $(,)? ) => {
+        const $array_name: [&str; 256] = {
+            let mut x = [""; 256];
             $(
                 let elements = $elements.as_bytes();
                 let mut binary_value = 1;
@@ -30,31 +90,77 @@ macro_rules! to_script {
                 element_to_binary_digit!(binary_value, elements, 2);
                 element_to_binary_digit!(binary_value, elements, 1);
                 element_to_binary_digit!(binary_value, elements, 0);
-                if x[binary_value as usize] != '\0' {
+                if !x[binary_value as usize].is_empty() {
                     panic!("Conflict between binary values");
                 }
-                x[binary_value as usize] = $character;
+                x[binary_value as usize] = $text;
             )+
-            x[0] = ' ';
+            x[0] = " ";
             x
         };
         $(#[$outer])*
         ///
-        /// This function should not be called directly, but passed as an argument to either
-        /// [decode_stream][crate::decode_stream] or [decode_string][crate::decode_string].
+        /// This function should not be called directly, but passed as a
+        /// [MorseAlphabet][crate::MorseAlphabet] to either [decode_stream][crate::decode_stream] or
+        /// [decode_string][crate::decode_string].
         ///
         /// The `elements` parameter represents up to 7 Morse code elements. There is one bit per
         /// element (0 for dot, 1 for dash), and a leading bit do detect the number of elements.
-        pub fn $function_name(elements: u8) -> char {
+        /// The empty string is returned for unknown sequences of elements.
+        pub fn $function_name(elements: u8) -> &'static str {
             $array_name[elements as usize]
         }
     };
 }
 
+macro_rules! to_prosigns {
+    ($array_name:ident, $($elements:expr => $prosign:expr),+ $(,)? ) => {
+        pub const $array_name: [&str; 256] = {
+            let mut x = [""; 256];
+            $(
+                let elements = $elements.as_bytes();
+                let mut binary_value = 1;
+                if elements.len() >= 8 { panic!("Too many elements"); }
+                element_to_binary_digit!(binary_value, elements, 6);
+                element_to_binary_digit!(binary_value, elements, 5);
+                element_to_binary_digit!(binary_value, elements, 4);
+                element_to_binary_digit!(binary_value, elements, 3);
+                element_to_binary_digit!(binary_value, elements, 2);
+                element_to_binary_digit!(binary_value, elements, 1);
+                element_to_binary_digit!(binary_value, elements, 0);
+                if !x[binary_value as usize].is_empty() {
+                    panic!("Conflict between binary values");
+                }
+                x[binary_value as usize] = $prosign;
+            )+
+            x
+        };
+    };
+}
+
+to_prosigns! {
+    PROSIGNS,
+    // Prosigns, in angle-bracket notation
+    // https://en.wikipedia.org/wiki/Prosigns_for_Morse_code
+    "..-.-" => "<INT>",  // interrogative
+    ".-..." => "<AS>",   // wait
+    ".-.-." => "<AR>",   // end of message, or '+'
+    "-...-" => "<BT>",   // break, or '='
+    "-.-.-" => "<KA>",   // start of message
+    "-.--." => "<KN>",   // invitation to a specific station to transmit, or '('
+    "...-." => "<SN>",   // understood, or 'Ŝ'
+    "....--" => "<HM>",  // halt
+    "...-.-" => "<SK>",  // end of contact
+    "..-..-" => "<IX>",
+}
+
 to_script! {
     /// Mapping from Morse code to Latin text
     TO_STANDARD,
     to_standard,
+    /// Mapping from Morse code to lowercase Latin text
+    lowercase TO_STANDARD_LOWERCASE,
+    to_standard_lowercase,
     // NOTE: Mappings are sorted like a complete binary tree in array representation. In other
     // words, they are sorted by length, then in lexicographic order. For lengths up to 5, all
     // possible combinations of Morse symbols are listed.
@@ -63,270 +169,280 @@ to_script! {
     // The ARRL handbook for the Radio Amateur (1985), 19-20 https://archive.org/details/arrlhandbookforr0000unse_w7j4/page/n433/mode/2up
 
     // One element
-    "." => 'E',
-    "-" => 'T',
+    "." => "E" | "e",
+    "-" => "T" | "t",
 
     // Two elements
-    ".." => 'I',
-    ".-" => 'A',
-    "-." => 'N',
-    "--" => 'M',
+    ".." => "I" | "i",
+    ".-" => "A" | "a",
+    "-." => "N" | "n",
+    "--" => "M" | "m",
 
     // Three elements
-    "..." => 'S',
-    "..-" => 'U',
-    ".-." => 'R',
-    ".--" => 'W',
-    "-.." => 'D',
-    "-.-" => 'K',
-    "--." => 'G',
-    "---" => 'O',
+    "..." => "S" | "s",
+    "..-" => "U" | "u",
+    ".-." => "R" | "r",
+    ".--" => "W" | "w",
+    "-.." => "D" | "d",
+    "-.-" => "K" | "k",
+    "--." => "G" | "g",
+    "---" => "O" | "o",
 
     // Four elements
-    "...." => 'H',
-    "...-" => 'V',
-    "..-." => 'F',
-    "..--" => 'Ü', // or 'Ŭ' according to Wikipedia
-    ".-.." => 'L',
-    ".-.-" => 'Ä', // or 'Æ' or 'Ą' according to Wikipedia
-    ".--." => 'P',
-    ".---" => 'J',
-    "-..." => 'B',
-    "-..-" => 'X',
-    "-.-." => 'C',
-    "-.--" => 'Y',
-    "--.." => 'Z',
-    "--.-" => 'Q',
-    "---." => 'Ö', // or 'Ó' or 'Ø', according to Wikipedia, or 'Œ' maybe
-    "----" => 'Ĥ', // according to Wikipedia, or 'Š' according to Wikipedia, or 'CH' (no single Unicode codepoint)
+    "...." => "H" | "h",
+    "...-" => "V" | "v",
+    "..-." => "F" | "f",
+    "..--" => "Ü" | "ü", // or 'Ŭ' according to Wikipedia
+    ".-.." => "L" | "l",
+    ".-.-" => "Ä" | "ä", // or 'Æ' or 'Ą' according to Wikipedia
+    ".--." => "P" | "p",
+    ".---" => "J" | "j",
+    "-..." => "B" | "b",
+    "-..-" => "X" | "x",
+    "-.-." => "C" | "c",
+    "-.--" => "Y" | "y",
+    "--.." => "Z" | "z",
+    "--.-" => "Q" | "q",
+    "---." => "Ö" | "ö", // or 'Ó' or 'Ø', according to Wikipedia, or 'Œ' maybe
+    "----" => "Ĥ" | "ĥ", // according to Wikipedia, or 'Š' according to Wikipedia, or 'CH' (no single Unicode codepoint)
 
     // Five elements
-    "....." => '5',
-    "....-" => '4',
-    "...-." => 'Ŝ', // according to Wikipedia, or '<SN>'
-    "...--" => '3',
-    "..-.." => 'É', // or 'Đ' according to Wikipedia, or 'Ę'
-    // "..-.-" => '<INT>', // prosign
-    "..--." => 'Ð', // according to Wikipedia
-    "..---" => '2',
-    // ".-..." => '<AS>',
-    ".-..-" => 'È', // according to Wikipedia, or 'Ł' according to Wikipedia, or '<AU>',
-    ".-.-." => '+', // or '<AR>'
+    "....." => "5",
+    "....-" => "4",
+    "...-." => "Ŝ" | "ŝ", // according to Wikipedia, or '<SN>'
+    "...--" => "3",
+    "..-.." => "É" | "é", // or 'Đ' according to Wikipedia, or 'Ę'
+    // "..-.-" => "<INT>", // prosign, see PROSIGNS
+    "..--." => "Ð" | "ð", // according to Wikipedia
+    "..---" => "2",
+    // ".-..." => "<AS>",
+    ".-..-" => "È" | "è", // according to Wikipedia, or 'Ł' according to Wikipedia, or '<AU>',
+    ".-.-." => "+", // or '<AR>'
     // .-.--
-    ".--.." => 'Þ', // according to Wikipedia
-    ".--.-" => 'Á', // or 'À' or 'Å'
-    ".---." => 'Ĵ', // according to Wikipedia, or 'Ì' according to Wikipedia
-    ".----" => '1',
-    "-...." => '6',
-    "-...-" => '=', // or '<BT>'
-    "-..-." => '/', // or '<DN>'
+    ".--.." => "Þ" | "þ", // according to Wikipedia
+    ".--.-" => "Á" | "á", // or 'À' or 'Å'
+    ".---." => "Ĵ" | "ĵ", // according to Wikipedia, or 'Ì' according to Wikipedia
+    ".----" => "1",
+    "-...." => "6",
+    "-...-" => "=", // or '<BT>'
+    "-..-." => "/", // or '<DN>'
     // -..--
-    "-.-.." => 'Ç', // or 'Ç' or 'Ć' according to Wikipedia
-    // "-.-.-" => '<KA>', // prosign
-    "-.--." => '(', // or '<KN>'
+    "-.-.." => "Ç" | "ç", // or 'Ç' or 'Ć' according to Wikipedia
+    // "-.-.-" => "<KA>", // prosign, see PROSIGNS
+    "-.--." => "(", // or '<KN>'
     // -.---
-    "--..." => '7',
-    "--..-" => 'Ź', // or 'Ż' according to Wikipedia
-    "--.-." => 'Ĝ', // according to Wikipedia; NOTE: missing from the ARRL handbook
-    "--.--" => 'Ñ', // or 'Ń' according to Wikipedia; NOTE: typo in the ARRL handbook incorrectly shows it encoded as --..--
-    "---.." => '8',
+    "--..." => "7",
+    "--..-" => "Ż" | "ż",
+    "--.-." => "Ĝ" | "ĝ", // according to Wikipedia; NOTE: missing from the ARRL handbook
+    "--.--" => "Ñ" | "ñ", // or 'Ń' according to Wikipedia; NOTE: typo in the ARRL handbook incorrectly shows it encoded as --..--
+    "---.." => "8",
     // ---.-
-    "----." => '9',
-    "-----" => '0',
+    "----." => "9",
+    "-----" => "0",
 
 
     // Six elements (only mapped)
-    // "....--" => '<HM>', // prosign
-    // "...-.-" => '<SK>', // prosign
-    // "..-..-" => '<IX>', // prosign
-    "..--.." => '?', // or '<IMI>'
-    "..--.-" => '_', // or '<IQ>'
-    ".-..-." => '"', // or '<AF>'
-    ".-.-.-" => '.', // or '<AAA>'
-    ".--.-." => '@', // added in 2004
-    ".----." => '\'', // or '<WG>'
-    "-....-" => '-', // or '<DU>'
-    "-.-.-." => ';', // or '<KR>'
-    "-.-.--" => '!', // according to Wikipedia
-    "-.--.-" => ')', // or '<KK>'
-    "--..--" => ',', // or '<MIM>'
-    "---..." => ':', // or '<OS>'
+    // "....--" => "<HM>", // prosign, see PROSIGNS
+    // "...-.-" => "<SK>", // prosign, see PROSIGNS
+    // "..-..-" => "<IX>", // prosign, see PROSIGNS
+    "..--.." => "?", // or '<IMI>'
+    "..--.-" => "_", // or '<IQ>'
+    ".-..-." => "\"", // or '<AF>'
+    ".-.-.-" => ".", // or '<AAA>'
+    ".--.-." => "@", // added in 2004
+    ".----." => "'", // or '<WG>'
+    "-....-" => "-", // or '<DU>'
+    "-.-.-." => ";", // or '<KR>'
+    "-.-.--" => "!", // according to Wikipedia
+    "-.--.-" => ")", // or '<KK>'
+    "--..-." => "Ź" | "ź", // according to Wikipedia
+    "--..--" => ",", // or '<MIM>'
+    "---..." => ":", // or '<OS>'
 
     // Seven elements (only mapped)
-    "...-..." => 'Ś', // according to Wikipedia
-    "...-..-" => '$', // or '<SX>'
-    "...--.." => 'ß', // according to Wikipedia
-    ".-----." => '`',
+    "...-..." => "Ś" | "ś", // according to Wikipedia
+    "...-..-" => "$", // or '<SX>'
+    "...--.." => "ß", // according to Wikipedia
+    ".-----." => "`",
 }
 
 to_script! {
     /// Mapping from Morse code to Greek text
     TO_GREEK,
     to_greek,
+    /// Mapping from Morse code to lowercase Greek text
+    lowercase TO_GREEK_LOWERCASE,
+    to_greek_lowercase,
     // Greek Morse code
     // Wikipedia: The Greek Morse code alphabet is very similar to the
     //            Latin alphabet. It uses one extra letter for Greek
     //            letter Χ and no longer uses the codes for Latin
     //            letters "J ", "U" and "V".
     // https://en.wikipedia.org/wiki/Morse_code_for_non-Latin_alphabets#Greek
-    ".-" => 'Α',
-    "-..." => 'Β',
-    "--." => 'Γ',
-    "-.." => 'Δ',
-    "." => 'Ε',
-    "--.." => 'Ζ',
-    "...." => 'Η',
-    "-.-." => 'Θ',
-    ".." => 'Ι',
-    "-.-" => 'Κ',
-    ".-.." => 'Λ',
-    "--" => 'Μ',
-    "-." => 'Ν',
-    "-..-" => 'Ξ',
-    "---" => 'Ο',
-    ".--." => 'Π',
-    ".-." => 'Ρ',
-    "..." => 'Σ',
-    "-" => 'Τ',
-    "-.--" => 'Υ',
-    "..-." => 'Φ',
-    "----" => 'Χ',
-    "--.-" => 'Ψ',
+    ".-" => "Α" | "α",
+    "-..." => "Β" | "β",
+    "--." => "Γ" | "γ",
+    "-.." => "Δ" | "δ",
+    "." => "Ε" | "ε",
+    "--.." => "Ζ" | "ζ",
+    "...." => "Η" | "η",
+    "-.-." => "Θ" | "θ",
+    ".." => "Ι" | "ι",
+    "-.-" => "Κ" | "κ",
+    ".-.." => "Λ" | "λ",
+    "--" => "Μ" | "μ",
+    "-." => "Ν" | "ν",
+    "-..-" => "Ξ" | "ξ",
+    "---" => "Ο" | "ο",
+    ".--." => "Π" | "π",
+    ".-." => "Ρ" | "ρ",
+    "..." => "Σ" | "σ",
+    "-" => "Τ" | "τ",
+    "-.--" => "Υ" | "υ",
+    "..-." => "Φ" | "φ",
+    "----" => "Χ" | "χ",
+    "--.-" => "Ψ" | "ψ",
 }
 
 to_script! {
     /// Mapping from Morse code to Russian (Cyrillic) text ([Russian Morse code](https://en.wikipedia.org/wiki/Russian_Morse_code))
     TO_RUSSIAN,
     to_russian,
+    /// Mapping from Morse code to lowercase Russian (Cyrillic) text
+    lowercase TO_RUSSIAN_LOWERCASE,
+    to_russian_lowercase,
     // Russian Morse code for Cyrillic
     // https://en.wikipedia.org/wiki/Russian_Morse_code (1857)
     // Полное собрание законов Российской Империи. Собрание Второе
     // These are listed in the order of the Wikipedia page (alphabetical
     // order of the corresponding Latin script character)
     // letters
-    ".-" => 'А',   // a
-    "-..." => 'Б', // be
-    ".--" => 'В',  // ve
-    "--." => 'Г',  // ghe
-    "-.." => 'Д',  // de
-    "." => 'Е',    // ie
-    "...-" => 'Ж', // zhe
-    "--.." => 'З', // ze
-    ".." => 'И',   // i
-    ".---" => 'Й', // short i
-    "-.-" => 'К',  // ka
-    ".-.." => 'Л', // el
-    "--" => 'М',   // em
-    "-." => 'Н',   // en
-    "---" => 'О',  // o
-    ".--." => 'П', // pe
-    ".-." => 'Р',  // er
-    "..." => 'С',  // es
-    "-" => 'Т',
-    "..-" => 'У',   // u
-    "..-." => 'Ф',  // ef
-    "...." => 'Х',  // ha
-    "-.-." => 'Ц',  // tse
-    "---." => 'Ч',  // che
-    "----" => 'Ш',  // sha
-    "--.-" => 'Щ',  // shcha
-    "-..-" => 'Ъ',  // hard sign
-    "-.--" => 'Ы',  // yeru
-    "..-.." => 'Ѣ', // yat  in Wikipedia article and in Russian law document
-    "..--" => 'Ю',  // yu
-    ".-.-" => 'Я',  // ya
+    ".-" => "А" | "а",   // a
+    "-..." => "Б" | "б", // be
+    ".--" => "В" | "в",  // ve
+    "--." => "Г" | "г",  // ghe
+    "-.." => "Д" | "д",  // de
+    "." => "Е" | "е",    // ie
+    "...-" => "Ж" | "ж", // zhe
+    "--.." => "З" | "з", // ze
+    ".." => "И" | "и",   // i
+    ".---" => "Й" | "й", // short i
+    "-.-" => "К" | "к",  // ka
+    ".-.." => "Л" | "л", // el
+    "--" => "М" | "м",   // em
+    "-." => "Н" | "н",   // en
+    "---" => "О" | "о",  // o
+    ".--." => "П" | "п", // pe
+    ".-." => "Р" | "р",  // er
+    "..." => "С" | "с",  // es
+    "-" => "Т" | "т",
+    "..-" => "У" | "у",   // u
+    "..-." => "Ф" | "ф",  // ef
+    "...." => "Х" | "х",  // ha
+    "-.-." => "Ц" | "ц",  // tse
+    "---." => "Ч" | "ч",  // che
+    "----" => "Ш" | "ш",  // sha
+    "--.-" => "Щ" | "щ",  // shcha
+    "-..-" => "Ъ" | "ъ",  // hard sign
+    "-.--" => "Ы" | "ы",  // yeru
+    "..-.." => "Ѣ" | "ѣ", // yat  in Wikipedia article and in Russian law document
+    "..--" => "Ю" | "ю",  // yu
+    ".-.-" => "Я" | "я",  // ya
     // numbers
-    ".----" => '1',
-    "..---" => '2',
-    "...--" => '3',
-    "....-" => '4',
-    "....." => '5',
-    "-...." => '6',
-    "--..." => '7',
-    "---.." => '8',
-    "----." => '9',
-    "-----" => '0',
+    ".----" => "1",
+    "..---" => "2",
+    "...--" => "3",
+    "....-" => "4",
+    "....." => "5",
+    "-...." => "6",
+    "--..." => "7",
+    "---.." => "8",
+    "----." => "9",
+    "-----" => "0",
     // punctuation
-    "......" => '.',
-    ".-.-.-" => ',',
-    "---..." => ':',
-    "-.-.-" => ';',
-    "-.--.-" => '(', // or ')'
-    ".----." => '\'',
-    ".-..-." => '"',
-    "-....-" => '—',
-    "-..-." => '/',
-    "..--.." => '?',
-    "--..--" => '!',
-    "-...-" => '-',
-    // "........" => '', // error/redo
-    ".--.-." => '@',
+    "......" => ".",
+    ".-.-.-" => ",",
+    "---..." => ":",
+    "-.-.-" => ";",
+    "-.--.-" => "(", // or ')'
+    ".----." => "'",
+    ".-..-." => "\"",
+    "-....-" => "—",
+    "-..-." => "/",
+    "..--.." => "?",
+    "--..--" => "!",
+    "-...-" => "-",
+    // "........" => "", // error/redo, handled by the decoder (see `ErrorSignal`)
+    ".--.-." => "@",
 }
 
 to_script! {
     /// Mapping from Morse code to Japanese (Katakana) text ([Wabun code](https://en.wikipedia.org/wiki/Wabun_code))
     TO_JAPANESE,
     to_japanese,
+    /// Mapping from Morse code to Japanese (Hiragana) text ([Wabun code](https://en.wikipedia.org/wiki/Wabun_code))
+    hiragana TO_JAPANESE_HIRAGANA,
+    to_japanese_hiragana,
     // Wabun code for Japanese, tnx JE1TRV
     // https://en.wikipedia.org/wiki/Wabun_code
     // https://www.rfcafe.com/references/qst/japanese-morse-telegraph-code-sep-1942-qst.htm (1942)
     // https://web.archive.org/web/20220129114408/https://elaws.e-gov.go.jp/data/325M50080000017_20200622_502M60000008061/pict/S25F30901000017-001.pdf (1945?)
     // 1. Kanas without any diacritics (dakuten or handakuten)
-    ".-" => 'イ',    // i
-    ".-.-" => 'ロ',  // ro
-    "-..." => 'ハ',  // ha
-    "-.-." => 'ニ',  // ni
-    "-.." => 'ホ',   // ho
-    "." => 'ヘ',     // he
-    "..-.." => 'ト', // to
-    "..-." => 'チ',  // ti
-    "--." => 'リ',   // ri
-    "...." => 'ヌ',  // nu
-    "-.--." => 'ル', // ru
-    ".---" => 'ヲ',  // wo
-    "-.-" => 'ワ',   // wa
-    ".-.." => 'カ',  // ka
-    "--" => 'ヨ',    // yo
-    "-." => 'タ',    // ta
-    "---" => 'レ',   // re
-    "---." => 'ソ',  // so
-    ".--." => 'ツ',  // tu
-    "--.-" => 'ネ',  // ne
-    ".-." => 'ナ',   // na
-    "..." => 'ラ',   // ra
-    "-" => 'ム',     // mu
-    "..-" => 'ウ',   // u
-    ".-..-" => 'ヰ', // yi
-    "..--" => 'ノ',  // no
-    ".-..." => 'オ', // o
-    "...-" => 'ク',  // ku
-    ".--" => 'ヤ',   // ya
-    "-..-" => 'マ',  // ma
-    "-.--" => 'ケ',  // ke
-    "--.." => 'フ',  // fu
-    "----" => 'コ',  // ko
-    "-.---" => 'エ', // e
-    ".-.--" => 'テ', // te
-    "--.--" => 'ア', // a
-    "-.-.-" => 'サ', // sa
-    "-.-.." => 'キ', // ki
-    "-..--" => 'ユ', // yu
-    "-...-" => 'メ', // me
-    "..-.-" => 'ミ', // mi
-    "--.-." => 'シ', // si
-    ".--.." => 'ヱ', // ye
-    "--..-" => 'ヒ', // hi
-    "-..-." => 'モ', // mo
-    ".---." => 'セ', // se
-    "---.-" => 'ス', // su
-    ".-.-." => 'ン', // n
-    ".." => '゛',    // dakuten modifier
-    "..--." => '゜', // handakuten modifier
-    ".--.-" => 'ー',
-    "-.--.-" => '（',
-    ".-..-." => '）',
-    ".-.-.-" => '、',
-    ".-.-.." => '。',
+    ".-" => "イ",    // i
+    ".-.-" => "ロ",  // ro
+    "-..." => "ハ",  // ha
+    "-.-." => "ニ",  // ni
+    "-.." => "ホ",   // ho
+    "." => "ヘ",     // he
+    "..-.." => "ト", // to
+    "..-." => "チ",  // ti
+    "--." => "リ",   // ri
+    "...." => "ヌ",  // nu
+    "-.--." => "ル", // ru
+    ".---" => "ヲ",  // wo
+    "-.-" => "ワ",   // wa
+    ".-.." => "カ",  // ka
+    "--" => "ヨ",    // yo
+    "-." => "タ",    // ta
+    "---" => "レ",   // re
+    "---." => "ソ",  // so
+    ".--." => "ツ",  // tu
+    "--.-" => "ネ",  // ne
+    ".-." => "ナ",   // na
+    "..." => "ラ",   // ra
+    "-" => "ム",     // mu
+    "..-" => "ウ",   // u
+    ".-..-" => "ヰ", // yi
+    "..--" => "ノ",  // no
+    ".-..." => "オ", // o
+    "...-" => "ク",  // ku
+    ".--" => "ヤ",   // ya
+    "-..-" => "マ",  // ma
+    "-.--" => "ケ",  // ke
+    "--.." => "フ",  // fu
+    "----" => "コ",  // ko
+    "-.---" => "エ", // e
+    ".-.--" => "テ", // te
+    "--.--" => "ア", // a
+    "-.-.-" => "サ", // sa
+    "-.-.." => "キ", // ki
+    "-..--" => "ユ", // yu
+    "-...-" => "メ", // me
+    "..-.-" => "ミ", // mi
+    "--.-." => "シ", // si
+    ".--.." => "ヱ", // ye
+    "--..-" => "ヒ", // hi
+    "-..-." => "モ", // mo
+    ".---." => "セ", // se
+    "---.-" => "ス", // su
+    ".-.-." => "ン", // n
+    ".." => "゛",    // dakuten modifier
+    "..--." => "゜", // handakuten modifier
+    ".--.-" => "ー",
+    "-.--.-" => "（",
+    ".-..-." => "）",
+    ".-.-.-" => "、",
+    ".-.-.." => "。",
 }
 
 to_script! {
@@ -336,34 +452,34 @@ to_script! {
     // SKATS for Korean
     // The ARRL handbook for the radio amateur, 19-3 (1985)
     // https://archive.org/details/arrlhandbookforr0000unse_w7j4/page/n415/mode/2up
-    ".-.." => 'ㄱ',    // kiyeok
-    "..-." => 'ㄴ',    // nieun
-    "-..." => 'ㄷ',    // tikeut
-    "...-" => 'ㄹ',    // rieul
-    "--" => 'ㅁ',      // mieum
-    ".--" => 'ㅂ',     // pieup
-    "--." => 'ㅅ',     // sios
-    "-.-" => 'ㅇ',     // ieung
-    ".--." => 'ㅈ',    // cieuc
-    "-.-." => 'ㅊ',    // chieuch
-    "-..-" => 'ㅋ',    // khieukh
-    "--.." => 'ㅌ',    // thieuth
-    "---" => 'ㅍ',     // phieuph
-    ".---" => 'ㅎ',    // hieuh
-    "." => 'ㅏ',       // a
-    "--.-" => 'ㅐ',    // ae
-    ".." => 'ㅑ',      // ya
-    //".. ..-" => 'ㅒ',  // yae
-    "-" => 'ㅓ',       // eo
-    "-.--" => 'ㅔ',    // e
-    "..." => 'ㅕ',     // yeo
-    //"... ..-" => 'ㅖ', // ye
-    ".-" => 'ㅗ',      // o
-    "-." => 'ㅛ',      // yo
-    "...." => 'ㅜ',    // u
-    ".-." => 'ㅠ',     // yu
-    "-.." => 'ㅡ',     // eu
-    "..-" => 'ㅣ',     // i
+    ".-.." => "ㄱ",    // kiyeok
+    "..-." => "ㄴ",    // nieun
+    "-..." => "ㄷ",    // tikeut
+    "...-" => "ㄹ",    // rieul
+    "--" => "ㅁ",      // mieum
+    ".--" => "ㅂ",     // pieup
+    "--." => "ㅅ",     // sios
+    "-.-" => "ㅇ",     // ieung
+    ".--." => "ㅈ",    // cieuc
+    "-.-." => "ㅊ",    // chieuch
+    "-..-" => "ㅋ",    // khieukh
+    "--.." => "ㅌ",    // thieuth
+    "---" => "ㅍ",     // phieuph
+    ".---" => "ㅎ",    // hieuh
+    "." => "ㅏ",       // a
+    "--.-" => "ㅐ",    // ae
+    ".." => "ㅑ",      // ya
+    //".. ..-" => "ㅒ",  // yae
+    "-" => "ㅓ",       // eo
+    "-.--" => "ㅔ",    // e
+    "..." => "ㅕ",     // yeo
+    //"... ..-" => "ㅖ", // ye
+    ".-" => "ㅗ",      // o
+    "-." => "ㅛ",      // yo
+    "...." => "ㅜ",    // u
+    ".-." => "ㅠ",     // yu
+    "-.." => "ㅡ",     // eu
+    "..-" => "ㅣ",     // i
 }
 
 to_script! {
@@ -373,28 +489,28 @@ to_script! {
     // Hebrew
     // The ARRL handbook for the radio amateur, 19-3 (1985)
     // https://archive.org/details/arrlhandbookforr0000unse_w7j4/page/n415/mode/2up
-    ".-" => 'א',   // alef
-    "-..." => 'ב', // bet
-    "--." => 'ג',  // gimel
-    "-.." => 'ד',  // dalet
-    "---" => 'ה',  // he
-    "." => 'ו',    // vav
-    "--.." => 'ז', // zayin
-    "...." => 'ח', // chet
-    "..-" => 'ט',  // tet
-    ".." => 'י',   // yod
-    "-.-" => 'כ',  // kaf
-    ".-.." => 'ל', // lamed
-    "--" => 'מ',   // mem
-    "-." => 'נ',   // nun
-    "-.-." => 'ס', // samekh
-    ".---" => 'ע', // ayin
-    ".--." => 'פ', // pe
-    ".--" => 'צ',  // tsadi
-    "--.-" => 'ק', // qof
-    ".-." => 'ר',  // resh
-    "..." => 'ש',  // dotless shin
-    "-" => 'ת',    // dotless tav
+    ".-" => "א",   // alef
+    "-..." => "ב", // bet
+    "--." => "ג",  // gimel
+    "-.." => "ד",  // dalet
+    "---" => "ה",  // he
+    "." => "ו",    // vav
+    "--.." => "ז", // zayin
+    "...." => "ח", // chet
+    "..-" => "ט",  // tet
+    ".." => "י",   // yod
+    "-.-" => "כ",  // kaf
+    ".-.." => "ל", // lamed
+    "--" => "מ",   // mem
+    "-." => "נ",   // nun
+    "-.-." => "ס", // samekh
+    ".---" => "ע", // ayin
+    ".--." => "פ", // pe
+    ".--" => "צ",  // tsadi
+    "--.-" => "ק", // qof
+    ".-." => "ר",  // resh
+    "..." => "ש",  // dotless shin
+    "-" => "ת",    // dotless tav
 }
 
 to_script! {
@@ -407,35 +523,76 @@ to_script! {
     // Unicode points were copied from “Isolated form”, and names from “Letter name” in
     // https://en.wikipedia.org/wiki/Arabic_alphabet#Table_of_basic_letters
     // TODO: add contextual forms
-    ".-" => 'ا',    // ʾalif
-    "-..." => 'ب',  // bāʾ/bah
-    "-" => 'ت',     // tāʾ/tah
-    "-.-." => 'ث',  // thāʾ/thah
-    ".---" => 'ج',  // jīm
-    "...." => 'ح',  // ḥāʾ/ḥah
-    "---" => 'خ',   // khāʾ/khah
-    "-.." => 'د',   // dāl/dāʾ/dah
-    "--.." => 'ذ',  // dhāl/dhāʾ/dhah
-    ".-." => 'ر',   // rāʾ/rah
-    "---." => 'ز',  // zāy/zayn/zāʾ/zah
-    "..." => 'س',   // sīn
-    "----" => 'ش',  // shīn
-    "-..-" => 'ص',  // ṣād
-    "...-" => 'ض',  // ḍād/ḍāʾ/ḍah
-    "..-" => 'ط',   // ṭāʾ/ṭah
-    "-.--" => 'ظ',  // ẓāʾ/ẓah
-    ".-.-" => 'ع',  // ʿayn
-    "--." => 'غ',   // ghayn
-    "..-." => 'ف',  // fāʾ/fah
-    "--.-" => 'ق',  // qāf
-    "-.-" => 'ڪ',   // kāf/kāʾ/kah
-    ".-.." => 'ل',  // lām
-    "--" => 'م',    // mīm
-    "-." => 'ن',    // nūn
-    "..-.." => 'ه', // hāʾ/hah
-    ".--" => 'و',   // wāw
-    ".." => 'ے',    // yāʾ/yah
-    //".-...-" => 'لا', // lām-alif (ligature)
+    ".-" => "ا",    // ʾalif
+    "-..." => "ب",  // bāʾ/bah
+    "-" => "ت",     // tāʾ/tah
+    "-.-." => "ث",  // thāʾ/thah
+    ".---" => "ج",  // jīm
+    "...." => "ح",  // ḥāʾ/ḥah
+    "---" => "خ",   // khāʾ/khah
+    "-.." => "د",   // dāl/dāʾ/dah
+    "--.." => "ذ",  // dhāl/dhāʾ/dhah
+    ".-." => "ر",   // rāʾ/rah
+    "---." => "ز",  // zāy/zayn/zāʾ/zah
+    "..." => "س",   // sīn
+    "----" => "ش",  // shīn
+    "-..-" => "ص",  // ṣād
+    "...-" => "ض",  // ḍād/ḍāʾ/ḍah
+    "..-" => "ط",   // ṭāʾ/ṭah
+    "-.--" => "ظ",  // ẓāʾ/ẓah
+    ".-.-" => "ع",  // ʿayn
+    "--." => "غ",   // ghayn
+    "..-." => "ف",  // fāʾ/fah
+    "--.-" => "ق",  // qāf
+    "-.-" => "ڪ",   // kāf/kāʾ/kah
+    ".-.." => "ل",  // lām
+    "--" => "م",    // mīm
+    "-." => "ن",    // nūn
+    "..-.." => "ه", // hāʾ/hah
+    ".--" => "و",   // wāw
+    ".." => "ے",    // yāʾ/yah
+    //".-...-" => "لا", // lām-alif (ligature)
     // other characters without a reference
-    "." => 'ء', // hamzah
+    "." => "ء", // hamzah
+}
+
+#[test]
+fn test_lowercase() {
+    let tables = [
+        (TO_STANDARD, TO_STANDARD_LOWERCASE),
+        (TO_GREEK, TO_GREEK_LOWERCASE),
+        (TO_RUSSIAN, TO_RUSSIAN_LOWERCASE),
+    ];
+    for (uppercase, lowercase) in tables {
+        for (upper, lower) in uppercase.iter().zip(lowercase) {
+            assert_eq!(lower, upper.to_lowercase());
+        }
+    }
+    let morse = crate::encode_string("Ñandú, 42!");
+    assert_eq!(
+        crate::decode_string(morse.as_bytes(), to_standard_lowercase),
+        "ñandu, 42!"
+    );
+    let morse = crate::encode_string_with("Ελληνες", crate::Variant::Greek);
+    assert_eq!(
+        crate::decode_string(morse.as_bytes(), to_greek_lowercase),
+        "ελληνεσ"
+    );
+}
+
+#[test]
+fn test_hiragana() {
+    for (katakana, hiragana) in TO_JAPANESE.iter().zip(TO_JAPANESE_HIRAGANA) {
+        let expected: String = katakana
+            .chars()
+            .map(|c| match c {
+                'ァ'..='ヶ' => char::from_u32(c as u32 - 0x60).unwrap(),
+                c => c,
+            })
+            .collect();
+        assert_eq!(hiragana, expected);
+    }
+    assert_eq!(to_japanese_hiragana(0b110), "い");
+    assert_eq!(to_japanese_hiragana(0b101010), "ん");
+    assert_eq!(to_japanese_hiragana(0b100), "゛");
 }
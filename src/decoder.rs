@@ -0,0 +1,305 @@
+use std::io::Read;
+
+use crate::decode::{DecodeOptions, MorseAlphabet, decode_buffer, decode_buffer_end, final_len};
+
+/// Incremental decoder of Morse code
+///
+/// Morse code is pushed into the decoder in pieces, and decoded like
+/// [decode_string_with][crate::decode_string_with] does. Each character is decoded as soon as the
+/// separator that follows it is pushed, so that the decoded text can be displayed immediately.
+/// The last character is decoded by [finish][Decoder::finish].
+///
+/// ```
+/// let mut decoder = ripmors::Decoder::new(ripmors::to_standard);
+/// assert_eq!(decoder.push(b"-- --- .-"), "MO");
+/// assert_eq!(decoder.push(b". ... . "), "RSE");
+/// assert_eq!(decoder.push(b"/ -.-. --- -.. ."), " COD");
+/// assert_eq!(decoder.finish(), "E");
+/// ```
+pub struct Decoder<A: MorseAlphabet> {
+    alphabet: A,
+    options: DecodeOptions,
+    /// Tokens recognized when converting to the default tokens, built once from `options`
+    tokens: Vec<(Vec<u8>, u8)>,
+    /// Morse code pushed, but not converted to the default tokens yet: an incomplete token
+    raw_buf: Vec<u8>,
+    /// Morse code pushed but not decoded yet, written with the default tokens
+    input_buf: Vec<u8>,
+    /// Decoded text, starting with the text returned by the last call to `push`
+    output_buf: Vec<u8>,
+    /// Length of the text returned by the last call to `push`
+    bytes_returned: usize,
+}
+
+impl<A: MorseAlphabet> Decoder<A> {
+    /// Create a decoder using `alphabet`
+    pub fn new(alphabet: A) -> Self {
+        Decoder::with_options(alphabet, DecodeOptions::default())
+    }
+
+    /// Create a decoder using `alphabet` and `options`
+    pub fn with_options(alphabet: A, options: DecodeOptions) -> Self {
+        Decoder {
+            alphabet,
+            tokens: options.normalization_tokens(),
+            options,
+            raw_buf: Vec::new(),
+            input_buf: Vec::new(),
+            output_buf: Vec::new(),
+            bytes_returned: 0,
+        }
+    }
+
+    /// Decode Morse code from `input`, and return the text that was decoded
+    ///
+    /// A sequence of elements at the end of `input` is only decoded once it is followed by a
+    /// separator. When decoding the error signal with [ErrorSignal::DeletePreviousWord], the
+    /// current line is only returned once complete, since it might still be edited.
+    ///
+    /// [ErrorSignal::DeletePreviousWord]: crate::ErrorSignal::DeletePreviousWord
+    pub fn push(&mut self, input: &[u8]) -> &str {
+        self.output_buf.drain(..self.bytes_returned);
+        if self.options.needs_normalization() {
+            self.raw_buf.extend_from_slice(input);
+            let bytes_used =
+                self.options
+                    .normalize(&self.tokens, &self.raw_buf, false, &mut self.input_buf);
+            self.raw_buf.drain(..bytes_used);
+        }
+        if !self.options.needs_normalization() && self.input_buf.is_empty() {
+            let bytes_used =
+                decode_buffer::<false>(input, &self.alphabet, &self.options, &mut self.output_buf)
+                    .expect("only strict decoding reports unknown sequences");
+            self.input_buf.extend_from_slice(&input[bytes_used..]);
+        } else {
+            if !self.options.needs_normalization() {
+                self.input_buf.extend_from_slice(input);
+            }
+            let bytes_used = decode_buffer::<false>(
+                &self.input_buf,
+                &self.alphabet,
+                &self.options,
+                &mut self.output_buf,
+            )
+            .expect("only strict decoding reports unknown sequences");
+            self.input_buf.drain(..bytes_used);
+        }
+        self.bytes_returned = final_len(&self.output_buf, &self.options);
+        // SAFETY: `decode_buffer` only outputs string slices, and ASCII characters, and
+        // `final_len` only splits after a line feed, or before a kana checked to be valid UTF-8
+        unsafe { std::str::from_utf8_unchecked(&self.output_buf[..self.bytes_returned]) }
+    }
+
+    /// Decode the Morse code pushed so far, and return the text that was not returned yet
+    ///
+    /// The decoder is then ready to decode a new message.
+    pub fn finish(&mut self) -> &str {
+        self.output_buf.drain(..self.bytes_returned);
+        if !self.raw_buf.is_empty() {
+            self.options
+                .normalize(&self.tokens, &self.raw_buf, true, &mut self.input_buf);
+            self.raw_buf.clear();
+        }
+        decode_buffer_end::<false>(
+            &self.input_buf,
+            &self.alphabet,
+            &self.options,
+            &mut self.output_buf,
+        )
+        .expect("only strict decoding reports unknown sequences");
+        self.input_buf.clear();
+        self.bytes_returned = self.output_buf.len();
+        // SAFETY: `decode_buffer_end` only outputs string slices, and ASCII characters
+        unsafe { std::str::from_utf8_unchecked(&self.output_buf) }
+    }
+}
+
+/// Decoder of Morse code into UTF-8 text, as a [Read][std::io::Read] adapter.
+///
+/// Morse code is read from the inner reader, and decoded like
+/// [decode_stream_with][crate::decode_stream_with] does. A sequence of elements split across
+/// several reads is decoded once complete.
+///
+/// ```
+/// use std::io::BufRead;
+///
+/// let morse = b"-- --- .-. ... .\n-.-. --- -.. .\n";
+/// let decoder = ripmors::MorseDecoder::new(&morse[..], ripmors::to_standard);
+/// let lines: Vec<String> = std::io::BufReader::new(decoder)
+///     .lines()
+///     .collect::<Result<_, _>>()
+///     .unwrap();
+/// assert_eq!(lines, ["MORSE", "CODE"]);
+/// ```
+pub struct MorseDecoder<R: Read, A: MorseAlphabet> {
+    inner: R,
+    decoder: Decoder<A>,
+    /// Morse code read from `inner`
+    input_buf: Vec<u8>,
+    /// Decoded text, starting at `bytes_read`
+    output_buf: Vec<u8>,
+    bytes_read: usize,
+    /// Whether the inner reader is exhausted
+    finished: bool,
+}
+
+impl<R: Read, A: MorseAlphabet> MorseDecoder<R, A> {
+    /// Create a decoder reading Morse code from `inner`, and decoding it using `alphabet`
+    pub fn new(inner: R, alphabet: A) -> Self {
+        MorseDecoder::with_options(inner, alphabet, DecodeOptions::default())
+    }
+
+    /// Create a decoder reading Morse code from `inner`, and decoding it using `alphabet` and
+    /// `options`
+    pub fn with_options(inner: R, alphabet: A, options: DecodeOptions) -> Self {
+        MorseDecoder {
+            inner,
+            decoder: Decoder::with_options(alphabet, options),
+            input_buf: vec![0u8; 1 << 15],
+            output_buf: Vec::with_capacity(1 << 15),
+            bytes_read: 0,
+            finished: false,
+        }
+    }
+
+    /// Get a reference to the inner reader
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Get a mutable reference to the inner reader
+    ///
+    /// Reading directly from the inner reader skips the Morse code read from it.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Return the inner reader, dropping any Morse code read from it but not decoded yet
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// Read more Morse code from the inner reader, and decode it
+    fn fill(&mut self) -> Result<(), std::io::Error> {
+        self.output_buf.clear();
+        self.bytes_read = 0;
+        let bytes_read = self.inner.read(&mut self.input_buf)?;
+        let decoded = if bytes_read == 0 {
+            self.finished = true;
+            self.decoder.finish()
+        } else {
+            self.decoder.push(&self.input_buf[..bytes_read])
+        };
+        self.output_buf.extend_from_slice(decoded.as_bytes());
+        Ok(())
+    }
+}
+
+impl<R: Read, A: MorseAlphabet> Read for MorseDecoder<R, A> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, std::io::Error> {
+        while self.bytes_read == self.output_buf.len() {
+            if self.finished || buf.is_empty() {
+                return Ok(0);
+            }
+            self.fill()?;
+        }
+        let available = &self.output_buf[self.bytes_read..];
+        let n = available.len().min(buf.len());
+        buf[..n].copy_from_slice(&available[..n]);
+        self.bytes_read += n;
+        Ok(n)
+    }
+}
+
+#[test]
+fn test_morse_decoder() {
+    use crate::{ErrorSignal, decode_string, encode_string, to_russian, to_standard};
+
+    let text = "THE QUICK BROWN FOX JUMPS OVER THE LAZY DOG\n".repeat(2000);
+    let morse = encode_string(&text);
+    let mut decoded = String::new();
+    MorseDecoder::new(morse.as_bytes(), to_standard)
+        .read_to_string(&mut decoded)
+        .unwrap();
+    assert_eq!(decoded, text);
+
+    // tiny reads, splitting UTF-8 sequences
+    let morse = b".--. .-. .. .-- . - / -- .. .-. \n";
+    let mut decoder = MorseDecoder::new(&morse[..], to_russian);
+    let mut decoded = Vec::new();
+    let mut buf = [0u8; 1];
+    while decoder.read(&mut buf).unwrap() != 0 {
+        decoded.push(buf[0]);
+    }
+    assert_eq!(decoded, decode_string(morse, to_russian).as_bytes());
+
+    // the current line is held back until it cannot be edited anymore
+    let options = DecodeOptions {
+        error_signal: ErrorSignal::DeletePreviousWord,
+        ..Default::default()
+    };
+    let morse = b"-- --- .-. ... . / -.-. --- -.. ........ / .-- --- .-. -..\n.- -...";
+    let mut decoded = String::new();
+    MorseDecoder::with_options(&morse[..], to_standard, options)
+        .read_to_string(&mut decoded)
+        .unwrap();
+    assert_eq!(decoded, "MORSE WORD\nAB");
+}
+
+#[test]
+fn test_decoder() {
+    use crate::{ErrorSignal, decode_string, encode_string, to_russian, to_standard};
+
+    let text = "Hello, World! Привет, мир!\n\tSOS";
+    let morse = encode_string(text);
+    let expected = decode_string(morse.as_bytes(), to_standard);
+    let bytes = morse.as_bytes();
+    for i in 0..=bytes.len() {
+        let mut decoder = Decoder::new(to_standard);
+        let mut decoded = decoder.push(&bytes[..i]).to_string();
+        decoded += decoder.push(&bytes[i..]);
+        decoded += decoder.finish();
+        assert_eq!(decoded, expected);
+    }
+
+    // characters are decoded as soon as possible
+    let mut decoder = Decoder::new(to_russian);
+    assert_eq!(decoder.push(b".--. .-. .. .-- . -"), "ПРИВЕ");
+    assert_eq!(decoder.push(b" "), "Т");
+    assert_eq!(decoder.push(b"/ -- .. .-."), " МИ");
+    assert_eq!(decoder.finish(), "Р");
+    // the decoder can be reused
+    assert_eq!(decoder.push(b"-.. .- "), "ДА");
+    assert_eq!(decoder.finish(), "");
+
+    // the current line is held back until it cannot be edited anymore
+    let options = DecodeOptions {
+        error_signal: ErrorSignal::DeletePreviousWord,
+        ..Default::default()
+    };
+    let mut decoder = Decoder::with_options(to_standard, options);
+    assert_eq!(decoder.push(b"... --- ... / -.-. --- -.. "), "");
+    assert_eq!(
+        decoder.push(b"........ / .-- --- .-. -..\n.- "),
+        "SOS WORD\n"
+    );
+    assert_eq!(decoder.finish(), "A");
+
+    // custom tokens, split across pushes
+    let options = DecodeOptions {
+        format: crate::MorseFormat {
+            dot: "·".into(),
+            dash: "−".into(),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let mut decoder = Decoder::with_options(to_standard, options);
+    let morse = "·−·· −−− ·−·· / ·−−·".as_bytes();
+    let mut decoded = String::new();
+    for byte in morse {
+        decoded += decoder.push(&[*byte]);
+    }
+    decoded += decoder.finish();
+    assert_eq!(decoded, "LOL P");
+}
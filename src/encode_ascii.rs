@@ -1,8 +1,9 @@
 use std::io::{Read, Write};
 
 use crate::encode_ascii_mapping::ASCII_TO_QWORD;
+use crate::encode_unicode::{Prosign, encode_prosign, find_angle_bracket, parse_prosign};
 
-fn encode_buffer_ascii(input: &[u8], output_buf: &mut Vec<u8>) {
+fn encode_bytes_ascii(input: &[u8], output_buf: &mut Vec<u8>) {
     let mut cur = output_buf.len();
     output_buf.reserve(input.len() * 18 + cur);
     for c in input {
@@ -36,6 +37,32 @@ fn encode_buffer_ascii(input: &[u8], output_buf: &mut Vec<u8>) {
     unsafe { output_buf.set_len(cur) };
 }
 
+/// Encode `input` into `output_buf`, return the number of bytes of `input` that were consumed
+///
+/// When `is_final` is false, a prosign that might continue after the end of `input` is left
+/// unconsumed.
+fn encode_buffer_ascii(input: &[u8], is_final: bool, output_buf: &mut Vec<u8>) -> usize {
+    let mut start = 0;
+    // prosigns are rare, so the text between them is encoded without looking for them
+    while let Some(offset) = find_angle_bracket(&input[start..]) {
+        let bracket = start + offset;
+        encode_bytes_ascii(&input[start..bracket], output_buf);
+        match parse_prosign(&input[bracket..]) {
+            Prosign::Found(len) => {
+                encode_prosign(&input[bracket + 1..bracket + len - 1], output_buf);
+                start = bracket + len;
+            }
+            Prosign::Incomplete if !is_final => return bracket,
+            _ => {
+                encode_bytes_ascii(b"<", output_buf);
+                start = bracket + 1;
+            }
+        }
+    }
+    encode_bytes_ascii(&input[start..], output_buf);
+    input.len()
+}
+
 /// Encode ASCII characters from a [byte slice][slice] into a [String].
 ///
 /// Bytes from `input` are interpreted as ASCII characters. The following ASCII characters are used to
@@ -49,6 +76,8 @@ fn encode_buffer_ascii(input: &[u8], output_buf: &mut Vec<u8>) {
 ///
 /// ASCII characters that cannot be converted to Morse and non-ASCII bytes, such as UTF-8 encodings, are ignored.
 ///
+/// Prosigns in angle-bracket notation are encoded like in [encode_string][crate::encode_string].
+///
 /// For example, the program below encodes ASCII input.
 /// ```
 /// let morse = ripmors::encode_string_ascii(b"Morse code");
@@ -56,7 +85,7 @@ fn encode_buffer_ascii(input: &[u8], output_buf: &mut Vec<u8>) {
 /// ```
 pub fn encode_string_ascii(input: &[u8]) -> String {
     let mut output_buf = Vec::new();
-    encode_buffer_ascii(input, &mut output_buf);
+    encode_buffer_ascii(input, true, &mut output_buf);
     output_buf.pop_if(|c| *c == b' ');
     // SAFETY: encode_buffer_ascii only outputs ASCII, so it is valid UTF-8
     unsafe { String::from_utf8_unchecked(output_buf) }
@@ -75,6 +104,8 @@ pub fn encode_string_ascii(input: &[u8]) -> String {
 ///
 /// ASCII characters that cannot be converted to Morse and non-ASCII bytes, such as UTF-8 encodings, are ignored.
 ///
+/// Prosigns in angle-bracket notation are encoded like in [encode_stream][crate::encode_stream].
+///
 /// **Note:** This will read data from `input` until exhaustion.
 ///
 /// For example, the program below encodes ASCII input.
@@ -91,13 +122,18 @@ pub fn encode_stream_ascii(
     output: &mut impl Write,
 ) -> Result<(), std::io::Error> {
     let mut input_buf = vec![0u8; 1 << 15];
+    let mut bytes_available = 0;
     let mut output_buf = Vec::new();
     loop {
-        let bytes_read = input.read(&mut input_buf)?;
+        let bytes_read = input.read(&mut input_buf[bytes_available..])?;
         if bytes_read == 0 {
             break;
         }
-        encode_buffer_ascii(&input_buf[..bytes_read], &mut output_buf);
+        bytes_available += bytes_read;
+        let bytes_encoded =
+            encode_buffer_ascii(&input_buf[..bytes_available], false, &mut output_buf);
+        input_buf.copy_within(bytes_encoded..bytes_available, 0);
+        bytes_available -= bytes_encoded;
         if output_buf.is_empty() {
         } else if output_buf.last() == Some(&b' ') {
             output_buf.pop();
@@ -109,6 +145,12 @@ pub fn encode_stream_ascii(
             output_buf.clear();
         }
     }
+    if bytes_available != 0 {
+        // encode what remains of a prosign
+        encode_buffer_ascii(&input_buf[..bytes_available], true, &mut output_buf);
+        output_buf.pop_if(|c| *c == b' ');
+        output.write_all(&output_buf)?;
+    }
     Ok(())
 }
 
@@ -146,3 +188,24 @@ fn test_ascii_encode_random_large() {
         .collect();
     encode_string_ascii(&data);
 }
+
+#[test]
+fn test_ascii_encode_prosign() {
+    use crate::encode_string;
+
+    let text = "73 <SK> <sk><AR>CQ<BT>DE a<br>b <SOS> <S K> <INT <SK";
+    assert_eq!(encode_string_ascii(text.as_bytes()), encode_string(text));
+    assert_eq!(encode_string_ascii(b"<SK>"), "...-.-");
+
+    // split prosigns at buffer boundaries
+    let f = |s: &str| {
+        let mut output = Vec::new();
+        encode_stream_ascii(&mut s.as_bytes(), &mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    };
+    assert_eq!(f("<SK"), "-.--. ... -.-");
+    for offset in 0..8 {
+        let input = format!("{}<SK> <INT>", "e".repeat((1 << 15) - offset));
+        assert_eq!(f(&input), encode_string(&input));
+    }
+}
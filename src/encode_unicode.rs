@@ -1,9 +1,105 @@
 use std::io::{Read, Write};
 
-use crate::encode_ascii_mapping::ASCII_TO_QWORD;
+use crate::decode_mapping::PROSIGNS;
+use crate::encode_ascii_mapping::{ASCII_TO_BYTES, ASCII_TO_QWORD};
 use crate::encode_unicode_mapping::from_unicode;
+use crate::format::MorseFormat;
+use crate::variant::Variant;
 
-fn encode_buffer(input: &str, output_buf: &mut Vec<u8>) {
+#[derive(Debug, PartialEq)]
+pub(crate) enum Prosign {
+    /// Prosign of the given length in bytes, including the angle brackets
+    Found(usize),
+    Absent,
+    /// The input ends before we can tell whether this is a prosign
+    Incomplete,
+}
+
+/// Recognize a prosign in angle-bracket notation, such as `<SK>`, at the start of `input`
+///
+/// Only the prosigns that can be decoded back, listed in [PROSIGNS], are recognized, in any case.
+#[cold]
+pub(crate) fn parse_prosign(input: &[u8]) -> Prosign {
+    debug_assert_eq!(input.first(), Some(&b'<'));
+    let mut incomplete = false;
+    for prosign in PROSIGNS.iter().filter(|prosign| !prosign.is_empty()) {
+        let prosign = prosign.as_bytes();
+        let len = prosign.len().min(input.len());
+        if input[..len].eq_ignore_ascii_case(&prosign[..len]) {
+            if len == prosign.len() {
+                return Prosign::Found(len);
+            }
+            incomplete = true;
+        }
+    }
+    if incomplete {
+        Prosign::Incomplete
+    } else {
+        Prosign::Absent
+    }
+}
+
+#[test]
+fn test_parse_prosign() {
+    assert_eq!(parse_prosign(b"<SK>"), Prosign::Found(4));
+    assert_eq!(parse_prosign(b"<int> ..."), Prosign::Found(5));
+    assert_eq!(parse_prosign(b"<SOS>"), Prosign::Absent);
+    assert_eq!(parse_prosign(b"<br>"), Prosign::Absent);
+    assert_eq!(parse_prosign(b"<>"), Prosign::Absent);
+    assert_eq!(parse_prosign(b"<K>"), Prosign::Absent);
+    assert_eq!(parse_prosign(b"<S K>"), Prosign::Absent);
+    assert_eq!(parse_prosign(b"<"), Prosign::Incomplete);
+    assert_eq!(parse_prosign(b"<SK"), Prosign::Incomplete);
+    assert_eq!(parse_prosign(b"<SX"), Prosign::Absent);
+}
+
+/// Return the position of the first `<` in `input`, which might start a prosign
+pub(crate) fn find_angle_bracket(input: &[u8]) -> Option<usize> {
+    // look for a zero byte in `chunk ^ pattern`, eight bytes at a time
+    const LOW_BITS: u64 = 0x0101010101010101;
+    const HIGH_BITS: u64 = 0x8080808080808080;
+    let pattern = LOW_BITS * u64::from(b'<');
+    let mut chunks = input.chunks_exact(8);
+    for (i, chunk) in chunks.by_ref().enumerate() {
+        let x = u64::from_le_bytes(chunk.try_into().unwrap()) ^ pattern;
+        if x.wrapping_sub(LOW_BITS) & !x & HIGH_BITS != 0 {
+            return chunk.iter().position(|&c| c == b'<').map(|j| 8 * i + j);
+        }
+    }
+    let rest = chunks.remainder();
+    let offset = input.len() - rest.len();
+    rest.iter().position(|&c| c == b'<').map(|j| offset + j)
+}
+
+#[test]
+fn test_find_angle_bracket() {
+    assert_eq!(find_angle_bracket(b""), None);
+    assert_eq!(find_angle_bracket(b"<"), Some(0));
+    assert_eq!(find_angle_bracket(b"Hello, World! <SK>"), Some(14));
+    assert_eq!(find_angle_bracket(b"Hello, World! =SK>"), None);
+    for i in 0..20 {
+        let mut input = vec![b'='; 20];
+        input[i] = b'<';
+        input[19] = b'<';
+        assert_eq!(find_angle_bracket(&input), Some(i));
+    }
+}
+
+/// Append the Morse code of the letters of a prosign to `output_buf`, without letter spaces
+/// between them
+pub(crate) fn encode_prosign(letters: &[u8], output_buf: &mut Vec<u8>) {
+    for &letter in letters {
+        let (bytes, len) = ASCII_TO_BYTES[letter as usize];
+        // without the letter space
+        output_buf.extend_from_slice(&bytes[..len - 1]);
+    }
+    output_buf.push(b' ');
+}
+
+/// Encode characters from `input` into `output_buf`, without looking for prosigns
+///
+/// With `OVERRIDES`, the encodings specific to `variant` take precedence.
+fn encode_chars<const OVERRIDES: bool>(input: &str, variant: Variant, output_buf: &mut Vec<u8>) {
     // SAFETY: `output_buf[cur]`
     // Accessing the element `cur` of `output_buf` is safe because
     // - `cur <= 18 * input_buf.len() + 1` because we increment `cur` by at most 18 for each byte read
@@ -11,6 +107,15 @@ fn encode_buffer(input: &str, output_buf: &mut Vec<u8>) {
     let mut cur = output_buf.len();
     output_buf.reserve(input.len() * 18 + cur);
     for c in input.chars() {
+        if OVERRIDES && let Some(bytes) = variant.encode_override(c) {
+            // SAFETY: overrides are at most 8 bytes long; see `output_buf[cur]` above
+            unsafe {
+                std::slice::from_raw_parts_mut(output_buf.as_mut_ptr().add(cur), bytes.len())
+            }
+            .copy_from_slice(bytes);
+            cur += bytes.len();
+            continue;
+        }
         if c.is_ascii() {
             let (bytes, len) = ASCII_TO_QWORD[c as usize];
             if len == 0 {
@@ -62,6 +167,52 @@ fn encode_buffer(input: &str, output_buf: &mut Vec<u8>) {
     unsafe { output_buf.set_len(cur) };
 }
 
+/// Encode characters from `input` into `output_buf`, using the conventions of `variant`, without
+/// looking for prosigns
+fn encode_chars_with(input: &str, variant: Variant, output_buf: &mut Vec<u8>) {
+    // most text is encoded with the standard variant, which has no overrides to look up
+    if variant == Variant::Standard {
+        encode_chars::<false>(input, variant, output_buf);
+    } else {
+        encode_chars::<true>(input, variant, output_buf);
+    }
+}
+
+/// Encode `input` into `output_buf`, return the number of bytes of `input` that were consumed
+///
+/// When `is_final` is false, a prosign that might continue after the end of `input` is left
+/// unconsumed.
+pub(crate) fn encode_buffer(
+    input: &str,
+    variant: Variant,
+    is_final: bool,
+    output_buf: &mut Vec<u8>,
+) -> usize {
+    let mut start = 0;
+    // prosigns are rare, so the text between them is encoded without looking for them
+    while let Some(offset) = find_angle_bracket(&input.as_bytes()[start..]) {
+        let bracket = start + offset;
+        encode_chars_with(&input[start..bracket], variant, output_buf);
+        match parse_prosign(&input.as_bytes()[bracket..]) {
+            Prosign::Found(len) => {
+                // NOTE: the prosign is made of ASCII characters only
+                encode_prosign(
+                    &input.as_bytes()[bracket + 1..bracket + len - 1],
+                    output_buf,
+                );
+                start = bracket + len;
+            }
+            Prosign::Incomplete if !is_final => return bracket,
+            _ => {
+                encode_chars_with("<", variant, output_buf);
+                start = bracket + 1;
+            }
+        }
+    }
+    encode_chars_with(&input[start..], variant, output_buf);
+    input.len()
+}
+
 /// Encode characters from a [string slice][&str] into a [String].
 ///
 /// The following ASCII characters are used to represent Morse code:
@@ -74,14 +225,38 @@ fn encode_buffer(input: &str, output_buf: &mut Vec<u8>) {
 ///
 /// Characters that cannot be converted to Morse are ignored.
 ///
+/// Prosigns can be written in angle-bracket notation: `<SK>` is encoded as `...-.-`, without
+/// letter space. Only the prosigns that [decode_string_with][crate::decode_string_with] renders
+/// with [prosigns][crate::DecodeOptions::prosigns] are recognized: `<AR>`, `<AS>`, `<BT>`, `<HM>`,
+/// `<INT>`, `<IX>`, `<KA>`, `<KN>`, `<SK>` and `<SN>`. Other text in angle brackets, such as
+/// `<br>`, is encoded character by character.
+///
 /// For example, the program below encodes a string slice.
 /// ```
 /// let morse = ripmors::encode_string("télégraphie");
 /// assert_eq!(morse, "- ..-.. .-.. ..-.. --. .-. .- .--. .... .. .");
+///
+/// let morse = ripmors::encode_string("73 <SK>");
+/// assert_eq!(morse, "--... ...-- / ...-.-");
 /// ```
 pub fn encode_string(input: &str) -> String {
+    encode_string_with(input, Variant::Standard)
+}
+
+/// Encode characters from a [string slice][&str] into a [String], using the conventions of a
+/// [Variant] of Morse code.
+///
+/// See [encode_string] for the format of the output.
+///
+/// ```
+/// use ripmors::Variant;
+///
+/// assert_eq!(ripmors::encode_string_with("(a).", Variant::Standard), "-.--. .- -.--.- .-.-.-");
+/// assert_eq!(ripmors::encode_string_with("(а).", Variant::Russian), "-.--.- .- -.--.- ......");
+/// ```
+pub fn encode_string_with(input: &str, variant: Variant) -> String {
     let mut output_buf = Vec::new();
-    encode_buffer(input, &mut output_buf);
+    encode_buffer(input, variant, true, &mut output_buf);
     output_buf.pop_if(|c| *c == b' ');
     // SAFETY: encode_buffer_ascii only outputs ASCII, so it is valid UTF-8
     unsafe { String::from_utf8_unchecked(output_buf) }
@@ -100,6 +275,12 @@ pub fn encode_string(input: &str) -> String {
 ///
 /// Unicode characters that cannot be converted to Morse are ignored.
 ///
+/// Prosigns can be written in angle-bracket notation: `<SK>` is encoded as `...-.-`, without
+/// letter space. Only the prosigns that [decode_string_with][crate::decode_string_with] renders
+/// with [prosigns][crate::DecodeOptions::prosigns] are recognized: `<AR>`, `<AS>`, `<BT>`, `<HM>`,
+/// `<INT>`, `<IX>`, `<KA>`, `<KN>`, `<SK>` and `<SN>`. Other text in angle brackets, such as
+/// `<br>`, is encoded character by character.
+///
 /// **Note:** This will read data from `input` until exhaustion.
 ///
 /// For example, the program below encodes an UTF-8 input.
@@ -112,44 +293,187 @@ pub fn encode_string(input: &str) -> String {
 /// }
 /// ```
 pub fn encode_stream(input: &mut impl Read, output: &mut impl Write) -> Result<(), std::io::Error> {
+    encode_stream_with(input, output, Variant::Standard)
+}
+
+/// Encode Unicode characters from a [Read][std::io::Read] object into a [Write][std::io::Write]
+/// object, using the conventions of a [Variant] of Morse code.
+///
+/// See [encode_stream] for the interpretation of `input` and the format of the output.
+///
+/// **Note:** This will read data from `input` until exhaustion.
+pub fn encode_stream_with(
+    input: &mut impl Read,
+    output: &mut impl Write,
+    variant: Variant,
+) -> Result<(), std::io::Error> {
+    let options = EncodeOptions {
+        variant,
+        ..Default::default()
+    };
+    encode_stream_with_options(input, output, &options)
+}
+
+/// Options controlling how text is encoded by [encode_string_with_options] and
+/// [encode_stream_with_options].
+///
+/// The default options give the same behavior as [encode_string] and [encode_stream].
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct EncodeOptions {
+    /// Conventions of a local variant of Morse code
+    pub variant: Variant,
+    /// Tokens used to write the Morse code
+    ///
+    /// The word separator is surrounded by letter separators, so that a word separator of `|`
+    /// gives ` | `, and a word separator of ` ` gives three spaces.
+    pub format: MorseFormat,
+    /// Encode consecutive spaces as a single word space, instead of one word space per space
+    pub collapse_spaces: bool,
+}
+
+/// Encode `input` into `output_buf` like [encode_buffer], with custom [EncodeOptions]; return the
+/// number of bytes of `input` that were consumed
+///
+/// `after_space` tells whether the text encoded so far ends with a space, and is updated.
+pub(crate) fn encode_buffer_with_options(
+    input: &str,
+    options: &EncodeOptions,
+    is_final: bool,
+    after_space: &mut bool,
+    output_buf: &mut Vec<u8>,
+) -> usize {
+    if !options.collapse_spaces {
+        return encode_buffer(input, options.variant, is_final, output_buf);
+    }
+    let mut collapsed = String::with_capacity(input.len());
+    for c in input.chars() {
+        if c == ' ' && *after_space {
+            continue;
+        }
+        *after_space = c == ' ';
+        collapsed.push(c);
+    }
+    let bytes_encoded = encode_buffer(&collapsed, options.variant, is_final, output_buf);
+    // what remains is the start of a prosign, which does not contain any space
+    input.len() - (collapsed.len() - bytes_encoded)
+}
+
+/// Encode characters from a [string slice][&str] into a [String], with custom [EncodeOptions].
+///
+/// See [encode_string] for the format of the output with the default options.
+///
+/// ```
+/// let options = ripmors::EncodeOptions {
+///     format: ripmors::MorseFormat {
+///         dot: "0".into(),
+///         dash: "1".into(),
+///         ..Default::default()
+///     },
+///     ..Default::default()
+/// };
+/// assert_eq!(ripmors::encode_string_with_options("SOS", &options), "000 111 000");
+///
+/// let options = ripmors::EncodeOptions {
+///     format: ripmors::MorseFormat {
+///         word_separator: "|".into(),
+///         ..Default::default()
+///     },
+///     collapse_spaces: true,
+///     ..Default::default()
+/// };
+/// assert_eq!(ripmors::encode_string_with_options("and  another", &options), ".- -. -.. | .- -. --- - .... . .-.");
+/// ```
+pub fn encode_string_with_options(input: &str, options: &EncodeOptions) -> String {
+    let mut output_buf = Vec::new();
+    encode_buffer_with_options(input, options, true, &mut false, &mut output_buf);
+    output_buf.pop_if(|c| *c == b' ');
+    if !options.format.is_default() {
+        let mut formatted = Vec::with_capacity(output_buf.len());
+        options.format.format(&output_buf, &mut formatted);
+        output_buf = formatted;
+    }
+    // SAFETY: encode_buffer only outputs ASCII, and tokens are string slices, so it is valid UTF-8
+    unsafe { String::from_utf8_unchecked(output_buf) }
+}
+
+/// Write the Morse code `morse` into `output`, using the tokens of `format`
+fn write_formatted(
+    output: &mut impl Write,
+    morse: &[u8],
+    format: &MorseFormat,
+    formatted: &mut Vec<u8>,
+) -> Result<(), std::io::Error> {
+    if format.is_default() {
+        output.write_all(morse)
+    } else {
+        formatted.clear();
+        format.format(morse, formatted);
+        output.write_all(formatted)
+    }
+}
+
+/// Encode Unicode characters from a [Read][std::io::Read] object into a [Write][std::io::Write]
+/// object, with custom [EncodeOptions].
+///
+/// See [encode_stream] for the interpretation of `input` and the format of the output with the
+/// default options.
+///
+/// **Note:** This will read data from `input` until exhaustion.
+pub fn encode_stream_with_options(
+    input: &mut impl Read,
+    output: &mut impl Write,
+    options: &EncodeOptions,
+) -> Result<(), std::io::Error> {
+    let format = &options.format;
+    let mut after_space = false;
     let mut input_buf = vec![0u8; 1 << 15];
     let mut bytes_available = 0;
     let mut output_buf = Vec::new();
+    let mut formatted = Vec::new();
     loop {
         let bytes_read = input.read(&mut input_buf[bytes_available..])?;
         if bytes_read == 0 {
             break;
         }
         bytes_available += bytes_read;
-        let (decoded, bytes_decoded) =
-            match simdutf8::compat::from_utf8(&input_buf[..bytes_available]) {
-                Ok(decoded) => (decoded, bytes_available),
-                Err(e) => {
-                    let bytes_decoded = e.valid_up_to();
-                    // SAFETY: we already checked that the string was valid UTF-8 up to
-                    // `bytes_decoded`
-                    let decoded =
-                        unsafe { core::str::from_utf8_unchecked(&input_buf[..bytes_decoded]) };
-                    (decoded, bytes_decoded)
-                }
-            };
-        encode_buffer(decoded, &mut output_buf);
+        let decoded = match simdutf8::compat::from_utf8(&input_buf[..bytes_available]) {
+            Ok(decoded) => decoded,
+            Err(e) => {
+                let bytes_decoded = e.valid_up_to();
+                // SAFETY: we already checked that the string was valid UTF-8 up to
+                // `bytes_decoded`
+                unsafe { core::str::from_utf8_unchecked(&input_buf[..bytes_decoded]) }
+            }
+        };
+        let bytes_encoded =
+            encode_buffer_with_options(decoded, options, false, &mut after_space, &mut output_buf);
         match output_buf.last() {
             Some(&b' ') => {
                 output_buf.pop();
-                output.write_all(&output_buf)?;
+                write_formatted(output, &output_buf, format, &mut formatted)?;
                 output_buf.clear();
                 output_buf.push(b' ');
             }
-            None => {
-                output.write_all(&output_buf)?;
+            _ => {
+                write_formatted(output, &output_buf, format, &mut formatted)?;
                 output_buf.clear();
             }
-            _ => (),
         }
-        input_buf.copy_within(bytes_decoded..bytes_available, 0);
-        bytes_available -= bytes_decoded;
+        input_buf.copy_within(bytes_encoded..bytes_available, 0);
+        bytes_available -= bytes_encoded;
     }
+    if bytes_available != 0 {
+        // encode what remains of a prosign, ignoring any incomplete UTF-8 sequence
+        let bytes_decoded = match simdutf8::compat::from_utf8(&input_buf[..bytes_available]) {
+            Ok(_) => bytes_available,
+            Err(e) => e.valid_up_to(),
+        };
+        // SAFETY: we already checked that the string was valid UTF-8 up to `bytes_decoded`
+        let decoded = unsafe { core::str::from_utf8_unchecked(&input_buf[..bytes_decoded]) };
+        encode_buffer_with_options(decoded, options, true, &mut after_space, &mut output_buf);
+    }
+    output_buf.pop_if(|c| *c == b' ');
+    write_formatted(output, &output_buf, format, &mut formatted)?;
     Ok(())
 }
 
@@ -169,6 +493,11 @@ fn test_unicode_encode() {
         encode_string("τηλεγραφία"),
         "- .... .-.. . --. .-. .- ..-. .. .-"
     );
+    assert_eq!(encode_string("ŹŻ"), "--..-. --..-");
+    assert_eq!(
+        crate::decode_string(b"--..-. --..-", crate::to_standard),
+        "ŹŻ"
+    );
     assert_eq!(
         encode_string("one line\nand  another\tline"),
         "--- -. . / .-.. .. -. .\n.- -. -.. / / .- -. --- - .... . .-.\t.-.. .. -. ."
@@ -198,3 +527,82 @@ fn test_unicode_encode_random_large() {
         .collect();
     encode_string(&data);
 }
+
+#[test]
+fn test_unicode_encode_prosign() {
+    assert_eq!(encode_string("<SK>"), "...-.-");
+    assert_eq!(encode_string("73 <sk>"), "--... ...-- / ...-.-");
+    assert_eq!(encode_string("<AR><BT>"), ".-.-. -...-");
+    assert_eq!(encode_string("CQ<BT>DE"), "-.-. --.- -...- -.. .");
+    // not prosigns
+    assert_eq!(encode_string("<K>"), "-.--. -.- -.--.-");
+    assert_eq!(encode_string("<S K>"), "-.--. ... / -.- -.--.-");
+    assert_eq!(encode_string("<SK"), "-.--. ... -.-");
+    assert_eq!(encode_string("a<br>b"), ".- -.--. -... .-. -.--.- -...");
+    assert_eq!(encode_string("<SOS>"), "-.--. ... --- ... -.--.-");
+}
+
+#[test]
+fn test_unicode_encode_stream() {
+    let f = |s: &str| {
+        let mut output = Vec::new();
+        encode_stream(&mut s.as_bytes(), &mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    };
+    assert_eq!(f("abc\n"), ".- -... -.-.\n");
+    assert_eq!(f("abc "), ".- -... -.-. /");
+    assert_eq!(f("<SK>"), "...-.-");
+    assert_eq!(f("<SK"), "-.--. ... -.-");
+
+    // split prosigns and UTF-8 sequences at buffer boundaries
+    for offset in 0..8 {
+        let input = format!("{}<SK> ü<INT>", "e".repeat((1 << 15) - offset));
+        assert_eq!(f(&input), encode_string(&input));
+    }
+}
+
+#[test]
+fn test_unicode_encode_format() {
+    let options = EncodeOptions {
+        variant: Variant::Russian,
+        format: MorseFormat {
+            dot: "·".into(),
+            dash: "−".into(),
+            letter_separator: " ".into(),
+            word_separator: "|".into(),
+        },
+        ..Default::default()
+    };
+    let expected = "·−−· ·−· ·· ·−− · − ······ | −− ·· ·−·\n";
+    assert_eq!(
+        encode_string_with_options("Привет. мир\n", &options),
+        expected
+    );
+    let mut output = Vec::new();
+    encode_stream_with_options(&mut "Привет. мир\n".as_bytes(), &mut output, &options).unwrap();
+    assert_eq!(output, expected.as_bytes());
+}
+
+#[test]
+fn test_unicode_encode_collapse_spaces() {
+    let options = EncodeOptions {
+        collapse_spaces: true,
+        ..Default::default()
+    };
+    let f = |s: &str| encode_string_with_options(s, &options);
+    assert_eq!(f("a  b"), ".- / -...");
+    assert_eq!(f("  a \t  b   "), "/ .- /\t/ -... /");
+    assert_eq!(f("<SK>   <AR>"), "...-.- / .-.-.");
+
+    // spaces split at buffer boundaries
+    for offset in 0..4 {
+        let input = format!("{}    <SK>  x", "e".repeat((1 << 15) - offset));
+        let mut output = Vec::new();
+        encode_stream_with_options(&mut input.as_bytes(), &mut output, &options).unwrap();
+        assert_eq!(output, f(&input).as_bytes());
+        assert_eq!(
+            output,
+            f(&input.replace("    ", " ").replace("  ", " ")).as_bytes()
+        );
+    }
+}
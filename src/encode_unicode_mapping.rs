@@ -62,7 +62,7 @@ pub fn from_unicode(c: char) -> (&'static [u8], usize) {
         'Ü' | 'ü' => "..--",
         'Ŭ' | 'ŭ' => "..--",
         'Ź' | 'ź' => "--..-.",
-        'Ż' | 'ż' => "--..-.",
+        'Ż' | 'ż' => "--..-",
         // not in Wikipedia article nor in ARRL handbook
         'ß' => "...--..",
         'Œ' | 'œ' => "---.",
@@ -0,0 +1,410 @@
+use std::io::Write;
+
+use crate::encode_unicode::{EncodeOptions, encode_buffer_with_options};
+use crate::variant::Variant;
+
+/// Length of the valid UTF-8 text at the start of `bytes`, ignoring an incomplete sequence
+fn valid_len(bytes: &[u8]) -> usize {
+    match simdutf8::compat::from_utf8(bytes) {
+        Ok(_) => bytes.len(),
+        Err(e) => e.valid_up_to(),
+    }
+}
+
+/// Incremental encoder of Unicode text into Morse code
+///
+/// Text is pushed into the encoder in pieces, and encoded like
+/// [encode_string_with][crate::encode_string_with] does: concatenating the Morse code returned by
+/// [push][Encoder::push] and by [finish][Encoder::finish] gives the same result as encoding the
+/// whole text at once. A prosign split across several pieces is encoded once complete, and the
+/// letter space after the last character is only returned when more text follows.
+///
+/// ```
+/// let mut encoder = ripmors::Encoder::new();
+/// assert_eq!(encoder.push("Morse "), "-- --- .-. ... . /");
+/// assert_eq!(encoder.push("code <S"), " -.-. --- -.. . /");
+/// assert_eq!(encoder.push("K>"), " ...-.-");
+/// assert_eq!(encoder.finish(), "");
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Encoder {
+    options: EncodeOptions,
+    /// Text pushed but not encoded yet: an incomplete prosign, followed by an incomplete UTF-8
+    /// sequence
+    input_buf: Vec<u8>,
+    /// Morse code, starting with the Morse code returned by the last call to `push`
+    output_buf: Vec<u8>,
+    /// Length of the Morse code returned by the last call to `push`
+    bytes_returned: usize,
+    /// Morse code returned, written with the tokens of `options.format`
+    formatted: Vec<u8>,
+    /// Whether the text encoded so far ends with a space
+    after_space: bool,
+}
+
+impl Encoder {
+    /// Create an encoder
+    pub fn new() -> Self {
+        Encoder::with_variant(Variant::Standard)
+    }
+
+    /// Create an encoder using the conventions of a [Variant]
+    pub fn with_variant(variant: Variant) -> Self {
+        Encoder::with_options(EncodeOptions {
+            variant,
+            ..Default::default()
+        })
+    }
+
+    /// Create an encoder with custom [EncodeOptions]
+    pub fn with_options(options: EncodeOptions) -> Self {
+        Encoder {
+            options,
+            input_buf: Vec::new(),
+            output_buf: Vec::new(),
+            bytes_returned: 0,
+            formatted: Vec::new(),
+            after_space: false,
+        }
+    }
+
+    /// Return the first `bytes_returned` bytes of Morse code, using the tokens of the format
+    fn formatted(&mut self) -> &str {
+        let morse = &self.output_buf[..self.bytes_returned];
+        let morse = if self.options.format.is_default() {
+            morse
+        } else {
+            self.formatted.clear();
+            self.options.format.format(morse, &mut self.formatted);
+            &self.formatted
+        };
+        // SAFETY: encode_buffer only outputs ASCII, and tokens are string slices, so it is valid
+        // UTF-8
+        unsafe { std::str::from_utf8_unchecked(morse) }
+    }
+
+    /// Return the Morse code encoded so far, except for a trailing letter space
+    fn output(&mut self) -> &str {
+        self.bytes_returned = match self.output_buf.last() {
+            Some(&b' ') => self.output_buf.len() - 1,
+            _ => self.output_buf.len(),
+        };
+        self.formatted()
+    }
+
+    /// Encode the text from `input`, and return the Morse code that was encoded
+    ///
+    /// An incomplete UTF-8 sequence left by [push_bytes][Encoder::push_bytes] is dropped.
+    pub fn push(&mut self, input: &str) -> &str {
+        self.output_buf.drain(..self.bytes_returned);
+        if self.input_buf.is_empty() {
+            let bytes_encoded = encode_buffer_with_options(
+                input,
+                &self.options,
+                false,
+                &mut self.after_space,
+                &mut self.output_buf,
+            );
+            self.input_buf
+                .extend_from_slice(&input.as_bytes()[bytes_encoded..]);
+        } else {
+            self.input_buf.truncate(valid_len(&self.input_buf));
+            self.input_buf.extend_from_slice(input.as_bytes());
+            // SAFETY: `input_buf` is the concatenation of valid UTF-8 strings
+            let text = unsafe { std::str::from_utf8_unchecked(&self.input_buf) };
+            let bytes_encoded = encode_buffer_with_options(
+                text,
+                &self.options,
+                false,
+                &mut self.after_space,
+                &mut self.output_buf,
+            );
+            self.input_buf.drain(..bytes_encoded);
+        }
+        self.output()
+    }
+
+    /// Encode the UTF-8 text from `input`, and return the Morse code that was encoded
+    ///
+    /// A UTF-8 sequence split across several pieces is encoded once complete. If `input` is not
+    /// valid UTF-8, nothing is encoded and an error is returned.
+    pub fn push_bytes(&mut self, input: &[u8]) -> Result<&str, std::str::Utf8Error> {
+        self.output_buf.drain(..self.bytes_returned);
+        self.bytes_returned = 0;
+        let bytes_pending = self.input_buf.len();
+        self.input_buf.extend_from_slice(input);
+        let bytes_decoded = match simdutf8::compat::from_utf8(&self.input_buf) {
+            Ok(_) => self.input_buf.len(),
+            // incomplete UTF-8 sequence at the end
+            Err(e) if e.error_len().is_none() => e.valid_up_to(),
+            Err(_) => {
+                let error = std::str::from_utf8(&self.input_buf).unwrap_err();
+                self.input_buf.truncate(bytes_pending);
+                return Err(error);
+            }
+        };
+        // SAFETY: we already checked that the string was valid UTF-8 up to `bytes_decoded`
+        let text = unsafe { std::str::from_utf8_unchecked(&self.input_buf[..bytes_decoded]) };
+        let bytes_encoded = encode_buffer_with_options(
+            text,
+            &self.options,
+            false,
+            &mut self.after_space,
+            &mut self.output_buf,
+        );
+        self.input_buf.drain(..bytes_encoded);
+        Ok(self.output())
+    }
+
+    /// Encode the remaining text, and return the Morse code that was not returned yet
+    ///
+    /// An incomplete UTF-8 sequence left by [push_bytes][Encoder::push_bytes] is dropped. The
+    /// encoder is then ready to encode a new message.
+    pub fn finish(&mut self) -> &str {
+        self.output_buf.drain(..self.bytes_returned);
+        let bytes_decoded = valid_len(&self.input_buf);
+        // SAFETY: we already checked that the string was valid UTF-8 up to `bytes_decoded`
+        let text = unsafe { std::str::from_utf8_unchecked(&self.input_buf[..bytes_decoded]) };
+        encode_buffer_with_options(
+            text,
+            &self.options,
+            true,
+            &mut self.after_space,
+            &mut self.output_buf,
+        );
+        self.input_buf.clear();
+        self.after_space = false;
+        self.output_buf.pop_if(|c| *c == b' ');
+        self.bytes_returned = self.output_buf.len();
+        self.formatted()
+    }
+}
+
+/// Encoder of Unicode text into Morse code, as a [Write][std::io::Write] adapter.
+///
+/// Bytes written into a `MorseEncoder` are interpreted as UTF-8 text, and encoded into the inner
+/// writer like [encode_stream][crate::encode_stream] does, using an [Encoder]. A UTF-8 sequence or
+/// a prosign split across several calls to [write][Write::write] is encoded once complete. Writing
+/// invalid UTF-8 is an error.
+///
+/// The letter space after the last character is only written when more text follows. When the
+/// encoder is dropped, or when calling [finish][MorseEncoder::finish], the remaining text is
+/// encoded without the trailing space.
+///
+/// ```
+/// use std::io::Write;
+///
+/// let mut encoder = ripmors::MorseEncoder::new(Vec::new());
+/// encoder.write_all("Morse ".as_bytes()).unwrap();
+/// encoder.write_all(&"código".as_bytes()[..2]).unwrap();
+/// encoder.write_all(&"código".as_bytes()[2..]).unwrap();
+/// let morse = encoder.finish().unwrap();
+/// assert_eq!(morse, b"-- --- .-. ... . / -.-. ---. -.. .. --. ---");
+/// ```
+pub struct MorseEncoder<W: Write> {
+    inner: Option<W>,
+    encoder: Encoder,
+}
+
+impl<W: Write> MorseEncoder<W> {
+    /// Create an encoder writing Morse code into `inner`
+    pub fn new(inner: W) -> Self {
+        MorseEncoder::with_variant(inner, Variant::Standard)
+    }
+
+    /// Create an encoder writing Morse code into `inner`, using the conventions of a [Variant]
+    pub fn with_variant(inner: W, variant: Variant) -> Self {
+        MorseEncoder::with_options(
+            inner,
+            EncodeOptions {
+                variant,
+                ..Default::default()
+            },
+        )
+    }
+
+    /// Create an encoder writing Morse code into `inner`, with custom [EncodeOptions]
+    pub fn with_options(inner: W, options: EncodeOptions) -> Self {
+        MorseEncoder {
+            inner: Some(inner),
+            encoder: Encoder::with_options(options),
+        }
+    }
+
+    /// Get a reference to the inner writer
+    pub fn get_ref(&self) -> &W {
+        self.inner
+            .as_ref()
+            .expect("inner writer is only taken by finish")
+    }
+
+    /// Get a mutable reference to the inner writer
+    ///
+    /// Writing directly to the inner writer might interleave with the Morse code that has not been
+    /// written yet.
+    pub fn get_mut(&mut self) -> &mut W {
+        self.inner
+            .as_mut()
+            .expect("inner writer is only taken by finish")
+    }
+
+    /// Encode the remaining text, and write it without the trailing letter space
+    fn finish_encoding(&mut self) -> Result<(), std::io::Error> {
+        let inner = self
+            .inner
+            .as_mut()
+            .expect("inner writer is only taken by finish");
+        inner.write_all(self.encoder.finish().as_bytes())?;
+        inner.flush()
+    }
+
+    /// Encode the remaining text, and return the inner writer
+    ///
+    /// Unlike dropping the encoder, this reports errors when writing the end of the Morse code.
+    pub fn finish(mut self) -> Result<W, std::io::Error> {
+        self.finish_encoding()?;
+        Ok(self
+            .inner
+            .take()
+            .expect("inner writer is only taken by finish"))
+    }
+}
+
+impl<W: Write> Write for MorseEncoder<W> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, std::io::Error> {
+        let morse = self.encoder.push_bytes(buf).map_err(|_| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "stream did not contain valid UTF-8",
+            )
+        })?;
+        let inner = self
+            .inner
+            .as_mut()
+            .expect("inner writer is only taken by finish");
+        inner.write_all(morse.as_bytes())?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> Result<(), std::io::Error> {
+        self.get_mut().flush()
+    }
+}
+
+impl<W: Write> Drop for MorseEncoder<W> {
+    fn drop(&mut self) {
+        if self.inner.is_some() {
+            // errors cannot be reported when dropping, see finish
+            let _ = self.finish_encoding();
+        }
+    }
+}
+
+#[test]
+fn test_encoder() {
+    use crate::encode_string;
+    let text = "Hello, wörld! <SK> でんしん\n\t<AR>";
+    let expected = encode_string(text);
+    let bytes = text.as_bytes();
+
+    // split at every possible position, including inside UTF-8 sequences and prosigns
+    for i in 0..=bytes.len() {
+        let mut encoder = Encoder::new();
+        let mut morse = encoder.push_bytes(&bytes[..i]).unwrap().to_string();
+        morse += encoder.push_bytes(&bytes[i..]).unwrap();
+        morse += encoder.finish();
+        assert_eq!(morse, expected);
+        if let Some((head, tail)) = text.split_at_checked(i) {
+            let mut morse = encoder.push(head).to_string();
+            morse += encoder.push(tail);
+            morse += encoder.finish();
+            assert_eq!(morse, expected);
+        }
+    }
+
+    let mut encoder = Encoder::with_variant(Variant::Russian);
+    assert_eq!(encoder.push("При"), ".--. .-. ..");
+    assert_eq!(encoder.push("вет."), " .-- . - ......");
+    assert_eq!(encoder.finish(), "");
+
+    // invalid UTF-8 is rejected, keeping what was pushed before
+    let mut encoder = Encoder::new();
+    assert_eq!(encoder.push_bytes(b"e\xe3"), Ok("."));
+    assert!(encoder.push_bytes(b"\xff").is_err());
+    assert_eq!(encoder.push_bytes(b"\x81\xa7"), Ok(" .-.-- .."));
+    assert_eq!(encoder.finish(), "");
+
+    let mut encoder = Encoder::with_options(EncodeOptions {
+        format: crate::MorseFormat {
+            dot: "di".into(),
+            dash: "dah".into(),
+            ..Default::default()
+        },
+        ..Default::default()
+    });
+    assert_eq!(encoder.push("ab"), "didah dahdididi");
+    assert_eq!(encoder.push("c"), " dahdidahdi");
+    assert_eq!(encoder.finish(), "");
+
+    let mut encoder = Encoder::with_options(EncodeOptions {
+        collapse_spaces: true,
+        ..Default::default()
+    });
+    assert_eq!(encoder.push("a "), ".- /");
+    assert_eq!(encoder.push(" "), "");
+    assert_eq!(encoder.push(" b"), " -...");
+    assert_eq!(encoder.finish(), "");
+}
+
+#[test]
+fn test_morse_encoder() {
+    use crate::encode_string;
+    let text = "Hello, wörld! <SK> でんしん\n\t<AR>";
+    let expected = encode_string(text);
+    let bytes = text.as_bytes();
+
+    // split at every possible position, including inside UTF-8 sequences and prosigns
+    for i in 0..=bytes.len() {
+        let mut encoder = MorseEncoder::new(Vec::new());
+        encoder.write_all(&bytes[..i]).unwrap();
+        encoder.write_all(&bytes[i..]).unwrap();
+        assert_eq!(encoder.finish().unwrap(), expected.as_bytes());
+    }
+
+    // byte by byte
+    let mut encoder = MorseEncoder::new(Vec::new());
+    for byte in bytes {
+        encoder.write_all(&[*byte]).unwrap();
+    }
+    assert_eq!(encoder.finish().unwrap(), expected.as_bytes());
+
+    // variant
+    let mut encoder = MorseEncoder::with_variant(Vec::new(), Variant::Russian);
+    encoder.write_all("Привет.".as_bytes()).unwrap();
+    assert_eq!(encoder.finish().unwrap(), b".--. .-. .. .-- . - ......");
+}
+
+#[test]
+fn test_morse_encoder_flush() {
+    let mut output = Vec::new();
+    {
+        let mut encoder = MorseEncoder::new(&mut output);
+        encoder.write_all(b"ab").unwrap();
+        encoder.flush().unwrap();
+        // the letter space is held back until more text comes
+        assert_eq!(encoder.get_ref().as_slice(), b".- -...");
+        encoder.write_all(b" c <S").unwrap();
+        encoder.flush().unwrap();
+        // the prosign might continue
+        assert_eq!(encoder.get_ref().as_slice(), b".- -... / -.-. /");
+        encoder.write_all(b"K>").unwrap();
+    }
+    // dropping the encoder writes the rest
+    assert_eq!(output, b".- -... / -.-. / ...-.-");
+
+    let mut encoder = MorseEncoder::new(Vec::new());
+    encoder.write_all(b"e\xe3").unwrap();
+    assert!(encoder.write_all(b"\xff").is_err());
+    // the incomplete sequence is dropped
+    assert_eq!(encoder.finish().unwrap(), b".");
+}
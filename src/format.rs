@@ -0,0 +1,381 @@
+use std::io::Read;
+
+use crate::decode::DecodeOptions;
+
+/// Alternate symbols accepted when decoding with
+/// [normalize_symbols][crate::DecodeOptions::normalize_symbols], along with the ASCII character
+/// they stand for
+const ALTERNATE_SYMBOLS: [(&[u8], u8); 6] = [
+    ("_".as_bytes(), b'-'),
+    ("–".as_bytes(), b'-'),
+    ("—".as_bytes(), b'-'),
+    ("−".as_bytes(), b'-'),
+    ("·".as_bytes(), b'.'),
+    ("•".as_bytes(), b'.'),
+];
+
+/// Tokens used to write Morse code as text, for encoding and decoding.
+///
+/// By default, Morse code is written with a full stop (.) for dots, a hyphen (-) for dashes, a
+/// space ( ) between letters, and a slash (/) for word spaces, as described in
+/// [encode_string][crate::encode_string] and [decode_string][crate::decode_string]. Other
+/// notations, such as `·` and `−`, `di` and `dah`, or `0` and `1`, can be used by changing the
+/// tokens. Tokens can be any non-empty string, including non-ASCII characters.
+///
+/// When encoding, each token replaces the corresponding ASCII character in the output. Tabs, line
+/// feeds and carriage returns are still kept as-is, and a word space is still surrounded by
+/// letter spaces.
+///
+/// When decoding, the input is split into tokens, preferring the longest one when several match.
+/// Spaces and ASCII control characters, such as line feeds, are interpreted as with the default
+/// tokens; other bytes are ignored. For the input to be unambiguous, the tokens should be
+/// distinct.
+///
+/// The default tokens are handled by a faster path, for both encoding and decoding.
+///
+/// ```
+/// let format = ripmors::MorseFormat {
+///     dot: "·".into(),
+///     dash: "−".into(),
+///     ..Default::default()
+/// };
+/// let options = ripmors::EncodeOptions {
+///     format: format.clone(),
+///     ..Default::default()
+/// };
+/// let morse = ripmors::encode_string_with_options("Morse code", &options);
+/// assert_eq!(morse, "−− −−− ·−· ··· · / −·−· −−− −·· ·");
+///
+/// let options = ripmors::DecodeOptions {
+///     format,
+///     ..Default::default()
+/// };
+/// let text = ripmors::decode_string_with(morse.as_bytes(), ripmors::to_standard, &options);
+/// assert_eq!(text, "MORSE CODE");
+/// ```
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct MorseFormat {
+    /// Token for a dot, `.` by default
+    pub dot: String,
+    /// Token for a dash, `-` by default
+    pub dash: String,
+    /// Token for a letter space, ` ` by default
+    pub letter_separator: String,
+    /// Token for a word space, `/` by default
+    pub word_separator: String,
+}
+
+impl Default for MorseFormat {
+    fn default() -> Self {
+        MorseFormat {
+            dot: ".".into(),
+            dash: "-".into(),
+            letter_separator: " ".into(),
+            word_separator: "/".into(),
+        }
+    }
+}
+
+impl MorseFormat {
+    /// Whether the tokens are the default ones, so that no conversion is needed
+    pub(crate) fn is_default(&self) -> bool {
+        self.dot == "."
+            && self.dash == "-"
+            && self.letter_separator == " "
+            && self.word_separator == "/"
+    }
+
+    /// Tokens, along with the ASCII character they stand for
+    fn tokens(&self) -> [(&[u8], u8); 4] {
+        [
+            (self.dot.as_bytes(), b'.'),
+            (self.dash.as_bytes(), b'-'),
+            (self.letter_separator.as_bytes(), b' '),
+            (self.word_separator.as_bytes(), b'/'),
+        ]
+    }
+
+    /// Append the Morse code `morse`, written with the default tokens, to `output_buf`, using
+    /// these tokens instead
+    pub(crate) fn format(&self, morse: &[u8], output_buf: &mut Vec<u8>) {
+        for &c in morse {
+            match c {
+                b'.' => output_buf.extend_from_slice(self.dot.as_bytes()),
+                b'-' => output_buf.extend_from_slice(self.dash.as_bytes()),
+                b' ' => output_buf.extend_from_slice(self.letter_separator.as_bytes()),
+                b'/' => output_buf.extend_from_slice(self.word_separator.as_bytes()),
+                c => output_buf.push(c),
+            }
+        }
+    }
+}
+
+/// Tokens recognized by [normalize] with `options`, along with the ASCII character they stand for
+pub(crate) fn normalization_tokens(options: &DecodeOptions) -> Vec<(Vec<u8>, u8)> {
+    // on ties, the last token wins, so the tokens of the format take precedence
+    let alternates: &[_] = if options.normalize_symbols {
+        &ALTERNATE_SYMBOLS
+    } else {
+        &[]
+    };
+    let bar: &[_] = if options.bar_word_gap {
+        &[("|".as_bytes(), b'/')]
+    } else {
+        &[]
+    };
+    (alternates.iter().chain(bar).copied())
+        .chain(options.format.tokens())
+        .map(|(token, c)| (token.to_vec(), c))
+        .collect()
+}
+
+/// Append the Morse code `input`, written as described by `options`, to `output_buf`, using the
+/// default tokens instead; return how many bytes of `input` were consumed
+///
+/// `tokens` are the [normalization_tokens] of `options`. When `is_final` is false, a token that
+/// might continue after the end of `input` is left unconsumed.
+pub(crate) fn normalize(
+    options: &DecodeOptions,
+    tokens: &[(Vec<u8>, u8)],
+    input: &[u8],
+    is_final: bool,
+    output_buf: &mut Vec<u8>,
+) -> usize {
+    let mut i = 0;
+    while i < input.len() {
+        let rest = &input[i..];
+        if options.spaces_word_gap && rest[0] == b' ' {
+            let spaces = rest.iter().position(|&c| c != b' ');
+            if spaces.is_none() && !is_final {
+                // the run of spaces might continue: only keep the last three spaces, which are
+                // enough to tell a word space once the run ends
+                i += rest.len().saturating_sub(3);
+                break;
+            }
+            let spaces = spaces.unwrap_or(rest.len());
+            if spaces >= 3 {
+                output_buf.extend_from_slice(b" / ");
+                i += spaces;
+                continue;
+            }
+        }
+        if !is_final
+            && tokens
+                .iter()
+                .any(|(token, _)| token.len() > rest.len() && token.starts_with(rest))
+        {
+            break;
+        }
+        let longest = tokens
+            .iter()
+            .filter(|(token, _)| !token.is_empty() && rest.starts_with(token))
+            .max_by_key(|(token, _)| token.len());
+        match longest {
+            // the sequence before a word space is only decoded when followed by a letter space, so
+            // new word space tokens are surrounded by letter spaces; a slash is kept as-is, so
+            // that it is decoded like without normalization
+            Some((token, b'/')) if token != b"/" => {
+                output_buf.extend_from_slice(b" / ");
+                i += token.len();
+            }
+            Some((token, c)) => {
+                output_buf.push(*c);
+                i += token.len();
+            }
+            // with the default tokens, other bytes are kept, so that they are decoded like without
+            // normalization; otherwise, they are part of the notation, like the t of dit
+            None => {
+                if rest[0] <= b' ' || options.format.is_default() {
+                    output_buf.push(rest[0]);
+                }
+                i += 1;
+            }
+        }
+    }
+    i
+}
+
+/// [Read][std::io::Read] adapter converting Morse code written with the tokens of a
+/// [MorseFormat] into Morse code written with the default tokens
+pub(crate) struct NormalizedReader<'a, R: Read> {
+    inner: &'a mut R,
+    options: &'a DecodeOptions,
+    tokens: Vec<(Vec<u8>, u8)>,
+    input_buf: Vec<u8>,
+    bytes_available: usize,
+    output_buf: Vec<u8>,
+    bytes_read: usize,
+    finished: bool,
+}
+
+impl<'a, R: Read> NormalizedReader<'a, R> {
+    pub(crate) fn new(inner: &'a mut R, options: &'a DecodeOptions) -> Self {
+        NormalizedReader {
+            inner,
+            options,
+            tokens: normalization_tokens(options),
+            input_buf: vec![0u8; 1 << 15],
+            bytes_available: 0,
+            output_buf: Vec::new(),
+            bytes_read: 0,
+            finished: false,
+        }
+    }
+}
+
+impl<R: Read> Read for NormalizedReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, std::io::Error> {
+        while self.bytes_read == self.output_buf.len() {
+            if self.finished || buf.is_empty() {
+                return Ok(0);
+            }
+            self.output_buf.clear();
+            self.bytes_read = 0;
+            if self.bytes_available == self.input_buf.len() {
+                // a single token fills the buffer
+                self.input_buf.resize(2 * self.input_buf.len(), 0);
+            }
+            let bytes_read = self
+                .inner
+                .read(&mut self.input_buf[self.bytes_available..])?;
+            self.bytes_available += bytes_read;
+            self.finished = bytes_read == 0;
+            let input = &self.input_buf[..self.bytes_available];
+            let bytes_used = normalize(
+                self.options,
+                &self.tokens,
+                input,
+                self.finished,
+                &mut self.output_buf,
+            );
+            self.input_buf
+                .copy_within(bytes_used..self.bytes_available, 0);
+            self.bytes_available -= bytes_used;
+        }
+        let available = &self.output_buf[self.bytes_read..];
+        let n = available.len().min(buf.len());
+        buf[..n].copy_from_slice(&available[..n]);
+        self.bytes_read += n;
+        Ok(n)
+    }
+}
+
+#[test]
+fn test_format() {
+    let format = MorseFormat {
+        dot: "di".into(),
+        dash: "dah".into(),
+        letter_separator: ", ".into(),
+        word_separator: "—".into(),
+    };
+    let mut output_buf = Vec::new();
+    format.format(b".- / -\n.", &mut output_buf);
+    assert_eq!(output_buf, "didah, —, dah\ndi".as_bytes());
+    assert!(MorseFormat::default().is_default());
+    assert!(!format.is_default());
+}
+
+#[test]
+fn test_normalize() {
+    let options = DecodeOptions {
+        format: MorseFormat {
+            dot: "di".into(),
+            dash: "dah".into(),
+            letter_separator: ", ".into(),
+            word_separator: "—".into(),
+        },
+        ..Default::default()
+    };
+    let f = |options: &DecodeOptions, input: &[u8], is_final| {
+        let mut output_buf = Vec::new();
+        let tokens = normalization_tokens(options);
+        let bytes_used = normalize(options, &tokens, input, is_final, &mut output_buf);
+        (String::from_utf8(output_buf).unwrap(), bytes_used)
+    };
+    assert_eq!(
+        f(&options, "didah, —, dah\ndi".as_bytes(), true),
+        (".-  /  -\n.".into(), 18)
+    );
+    // tokens split at the end of the input
+    assert_eq!(f(&options, b"didah, d", false), (".- ".into(), 7));
+    assert_eq!(f(&options, &"di—".as_bytes()[..3], false), (".".into(), 2));
+    assert_eq!(f(&options, b"di,", false), (".".into(), 2));
+    assert_eq!(f(&options, b"di,", true), (".".into(), 3));
+    // dashes of di-dah-dit are ignored, and the t of dit
+    assert_eq!(
+        f(&options, b"di-dah-dit di-dah", true),
+        (".-. .-".into(), 17)
+    );
+
+    // alternate symbols
+    let options = DecodeOptions {
+        normalize_symbols: true,
+        ..Default::default()
+    };
+    let input = "·–_ —•−. x.-".as_bytes();
+    assert_eq!(
+        f(&options, input, true),
+        (".-- -.-. x.-".into(), input.len())
+    );
+    // tokens of the format take precedence
+    let options = DecodeOptions {
+        normalize_symbols: true,
+        format: MorseFormat {
+            word_separator: "—".into(),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    assert_eq!(f(&options, "—·".as_bytes(), true), (" / .".into(), 5));
+
+    // word gaps
+    let options = DecodeOptions {
+        bar_word_gap: true,
+        spaces_word_gap: true,
+        ..Default::default()
+    };
+    assert_eq!(f(&options, b".-|-", true), (".- / -".into(), 4));
+    assert_eq!(f(&options, b".-/-", true), (".-/-".into(), 4));
+    assert_eq!(
+        f(&options, b". / -  .   -", true),
+        (". / -  . / -".into(), 12)
+    );
+    assert_eq!(f(&options, b".    ", true), (". / ".into(), 5));
+    // the run of spaces might continue
+    assert_eq!(f(&options, b".  ", false), (".".into(), 1));
+    assert_eq!(f(&options, b".     ", false), (".".into(), 3));
+}
+
+#[test]
+fn test_normalized_reader() {
+    let format = MorseFormat {
+        dot: "·".into(),
+        dash: "−".into(),
+        ..Default::default()
+    };
+    let mut output_buf = Vec::new();
+    let morse = "-- --- .-. ... . / -.-. --- -.. .\n".repeat(2000);
+    format.format(morse.as_bytes(), &mut output_buf);
+    let mut input = output_buf.as_slice();
+    let options = DecodeOptions {
+        format,
+        ..Default::default()
+    };
+    let mut normalized = Vec::new();
+    NormalizedReader::new(&mut input, &options)
+        .read_to_end(&mut normalized)
+        .unwrap();
+    assert_eq!(normalized, morse.as_bytes());
+
+    // runs of spaces longer than the buffer
+    let options = DecodeOptions {
+        spaces_word_gap: true,
+        ..Default::default()
+    };
+    let morse = format!("... {}--- ...", " ".repeat(40000));
+    let mut normalized = Vec::new();
+    NormalizedReader::new(&mut morse.as_bytes(), &options)
+        .read_to_end(&mut normalized)
+        .unwrap();
+    assert_eq!(normalized, b"... / --- ...");
+}
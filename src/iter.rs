@@ -0,0 +1,280 @@
+use std::ops::Range;
+
+use crate::decode::{DecodeOptions, MorseAlphabet, decode_chunk};
+use crate::encoder::Encoder;
+
+/// Number of bytes of text encoded at once by [encode_iter]
+const ENCODE_CHUNK: usize = 1 << 10;
+
+/// Element of Morse code, as returned by [encode_iter]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Symbol {
+    /// Dot, or dit
+    Dot,
+    /// Dash, or dah
+    Dash,
+    /// Space between two letters
+    LetterGap,
+    /// Space between two words
+    WordGap,
+    /// Tab (\t), line feed (\n) or carriage return (\r), passed through from the text
+    Whitespace(char),
+}
+
+/// Iterator over the Morse symbols of some text, see [encode_iter]
+#[derive(Clone, Debug)]
+pub struct EncodeIter<'a> {
+    /// Text not encoded yet
+    input: &'a str,
+    encoder: Encoder,
+    /// Morse code encoded but not returned yet
+    morse: Vec<u8>,
+    pos: usize,
+    /// Whether a letter space was seen since the last element
+    letter_gap: bool,
+    /// Whether the last symbol returned was a dot or a dash
+    after_element: bool,
+}
+
+impl EncodeIter<'_> {
+    /// Encode the next chunk of the input, return whether there is anything left to encode
+    fn fill(&mut self) -> bool {
+        if self.input.is_empty() {
+            return false;
+        }
+        let mut end = ENCODE_CHUNK.min(self.input.len());
+        while !self.input.is_char_boundary(end) {
+            end += 1;
+        }
+        let (chunk, rest) = self.input.split_at(end);
+        self.input = rest;
+        let mut morse = std::mem::take(&mut self.morse);
+        morse.clear();
+        morse.extend_from_slice(self.encoder.push(chunk).as_bytes());
+        if rest.is_empty() {
+            morse.extend_from_slice(self.encoder.finish().as_bytes());
+        }
+        self.morse = morse;
+        self.pos = 0;
+        true
+    }
+}
+
+impl Iterator for EncodeIter<'_> {
+    type Item = Symbol;
+
+    fn next(&mut self) -> Option<Symbol> {
+        loop {
+            while let Some(&c) = self.morse.get(self.pos) {
+                self.pos += 1;
+                let symbol = match c {
+                    b'.' => Symbol::Dot,
+                    b'-' => Symbol::Dash,
+                    b' ' => {
+                        self.letter_gap = self.after_element;
+                        continue;
+                    }
+                    b'/' => Symbol::WordGap,
+                    c => Symbol::Whitespace(char::from(c)),
+                };
+                let is_element = matches!(symbol, Symbol::Dot | Symbol::Dash);
+                if is_element && std::mem::take(&mut self.letter_gap) {
+                    // return the element on the next call
+                    self.pos -= 1;
+                    self.after_element = false;
+                    return Some(Symbol::LetterGap);
+                }
+                self.letter_gap = false;
+                self.after_element = is_element;
+                return Some(symbol);
+            }
+            if !self.fill() {
+                return None;
+            }
+        }
+    }
+}
+
+/// Encode characters from a [string slice][&str] into an iterator over Morse [symbols][Symbol].
+///
+/// The text is encoded lazily, a chunk at a time, like [encode_string][crate::encode_string]
+/// does. Elements of the same character follow each other without any symbol between them. A
+/// [LetterGap][Symbol::LetterGap] separates two characters of the same word, and is replaced by a
+/// [WordGap][Symbol::WordGap] for each space between words. Tabs, line feeds and carriage returns
+/// are passed through as [Whitespace][Symbol::Whitespace].
+///
+/// ```
+/// use ripmors::Symbol::*;
+///
+/// let symbols: Vec<_> = ripmors::encode_iter("Hi 5\n").collect();
+/// assert_eq!(
+///     symbols,
+///     [Dot, Dot, Dot, Dot, LetterGap, Dot, Dot, WordGap, Dot, Dot, Dot, Dot, Dot, Whitespace('\n')],
+/// );
+/// ```
+pub fn encode_iter(input: &str) -> EncodeIter<'_> {
+    EncodeIter {
+        input,
+        encoder: Encoder::new(),
+        morse: Vec::new(),
+        pos: 0,
+        letter_gap: false,
+        after_element: false,
+    }
+}
+
+/// Text decoded from a part of some Morse code, as returned by [decode_iter]
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct DecodedItem {
+    /// Byte range of the Morse code in the input
+    pub input: Range<usize>,
+    /// Decoded text: a character or a prosign for a sequence of elements, a space for a word
+    /// space, or the whitespace passed through. It is empty for a sequence of elements that does
+    /// not correspond to any character, and for the error signal.
+    pub text: String,
+}
+
+/// Iterator over the text decoded from some Morse code, see [decode_iter]
+#[derive(Clone, Debug)]
+pub struct DecodeIter<'a, A: MorseAlphabet> {
+    input: &'a [u8],
+    pos: usize,
+    alphabet: A,
+}
+
+impl<A: MorseAlphabet> Iterator for DecodeIter<'_, A> {
+    type Item = DecodedItem;
+
+    fn next(&mut self) -> Option<DecodedItem> {
+        while self.pos < self.input.len() {
+            let start = self.pos;
+            let end = self.input[start..]
+                .iter()
+                .position(|&c| c <= b' ' || c == b'/')
+                .map_or(self.input.len(), |len| start + len);
+            // like in `decode_buffer`, a sequence directly followed by a slash is dropped
+            if end != start && self.input[end..].first() != Some(&b'/') {
+                // sequence of elements
+                self.pos = end;
+                let mut text = Vec::new();
+                decode_chunk::<false>(
+                    self.input,
+                    start,
+                    end,
+                    &self.alphabet,
+                    &DecodeOptions::default(),
+                    &mut text,
+                )
+                .expect("only strict decoding reports unknown sequences");
+                // SAFETY: `decode_chunk` only outputs string slices
+                let text = unsafe { String::from_utf8_unchecked(text) };
+                return Some(DecodedItem {
+                    input: start..end,
+                    text,
+                });
+            }
+            self.pos = end + 1;
+            let text = match self.input[end] {
+                // letter space
+                b' ' => continue,
+                b'/' => ' ',
+                // tab, line feed, carriage return and other control characters are kept as-is
+                c => char::from(c),
+            };
+            return Some(DecodedItem {
+                input: end..end + 1,
+                text: text.into(),
+            });
+        }
+        None
+    }
+}
+
+/// Decode Morse code from a [byte slice][slice] into an iterator over [decoded items][DecodedItem].
+///
+/// Bytes from `input` are interpreted like in [decode_string][crate::decode_string], using
+/// `alphabet`, and the items are decoded lazily. Each sequence of elements, word space and
+/// whitespace gives one item, along with its position in `input`. Letter spaces give no item, and
+/// neither does a sequence directly followed by a slash, which is dropped by
+/// [decode_string][crate::decode_string].
+///
+/// ```
+/// let items: Vec<_> = ripmors::decode_iter(b"... --- / ..", ripmors::to_standard)
+///     .map(|item| (item.input, item.text))
+///     .collect();
+/// assert_eq!(
+///     items,
+///     [(0..3, "S".into()), (4..7, "O".into()), (8..9, " ".into()), (10..12, "I".into())],
+/// );
+/// ```
+pub fn decode_iter<A: MorseAlphabet>(input: &[u8], alphabet: A) -> DecodeIter<'_, A> {
+    DecodeIter {
+        input,
+        pos: 0,
+        alphabet,
+    }
+}
+
+#[test]
+fn test_encode_iter() {
+    use crate::encode_string;
+    use Symbol::*;
+
+    let f = |s| encode_iter(s).collect::<Vec<_>>();
+    assert_eq!(f(""), []);
+    assert_eq!(f("et"), [Dot, LetterGap, Dash]);
+    assert_eq!(f("e  t"), [Dot, WordGap, WordGap, Dash]);
+    assert_eq!(f(" e\t"), [WordGap, Dot, Whitespace('\t')]);
+    assert_eq!(f("<SK> e"), [Dot, Dot, Dot, Dash, Dot, Dash, WordGap, Dot]);
+
+    // symbols follow the Morse code, across chunks
+    let text = ["Hello, wörld! <SK> でんしん\n\t<AR>"; 100].join(" ");
+    let morse: String = encode_iter(&text)
+        .map(|symbol| match symbol {
+            Dot => ".",
+            Dash => "-",
+            LetterGap => " ",
+            WordGap => "/",
+            Whitespace('\n') => "\n",
+            Whitespace('\t') => "\t",
+            Whitespace(_) => unreachable!(),
+        })
+        .collect();
+    assert_eq!(morse, encode_string(&text).replace(" / ", "/"));
+}
+
+#[test]
+fn test_decode_iter() {
+    use crate::{decode_string, encode_string, to_standard};
+
+    let f = |s: &[u8]| {
+        decode_iter(s, to_standard)
+            .map(|item| (item.input, item.text))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(f(b""), []);
+    assert_eq!(f(b"  "), []);
+    assert_eq!(
+        f(b".-.-- -\n"),
+        [
+            (0..5, String::new()),
+            (6..7, "T".to_string()),
+            (7..8, "\n".to_string())
+        ]
+    );
+
+    // a sequence directly followed by a slash is dropped
+    assert_eq!(
+        f(b".-/-"),
+        [(2..3, " ".to_string()), (3..4, "T".to_string())]
+    );
+
+    // items follow the decoded text
+    let morse = encode_string("Hello, World! Привет, мир!\n\t<SK>");
+    for morse in [morse.as_bytes(), b".-/-", b"-- /.-/ / -.-./\n/.."] {
+        let text: String = decode_iter(morse, to_standard)
+            .map(|item| item.text)
+            .collect();
+        assert_eq!(text, decode_string(morse, to_standard));
+    }
+}
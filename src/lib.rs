@@ -11,20 +11,51 @@
 // Enforce "# Safety" section in documentation of unsafe functions
 #![warn(clippy::missing_safety_doc)]
 
+mod audio;
+mod audio_decode;
+mod code_table;
 mod decode;
 mod decode_mapping;
+mod decoder;
 mod encode_ascii;
 mod encode_ascii_mapping;
 mod encode_unicode;
 mod encode_unicode_mapping;
+mod encoder;
+mod format;
+mod iter;
+mod span;
+mod timing;
+mod timing_decode;
+mod variant;
+mod verify;
 
 // Public API
-pub use decode::{decode_stream, decode_string};
+pub use audio::{AudioOptions, Envelope, Timing, morse_to_wav, text_to_wav};
+pub use audio_decode::{decode_wav, wav_to_morse};
+pub use code_table::{CodeTable, CodeTableError, CodeTableErrorKind};
+pub use decode::{
+    DecodeError, DecodeOptions, ErrorSignal, MorseAlphabet, decode_stream, decode_stream_strict,
+    decode_stream_with, decode_string, decode_string_strict, decode_string_with,
+};
 pub use decode_mapping::{
-    to_arabic, to_greek, to_hebrew, to_japanese, to_korean, to_russian, to_standard,
+    to_arabic, to_greek, to_greek_lowercase, to_hebrew, to_japanese, to_japanese_hiragana,
+    to_korean, to_russian, to_russian_lowercase, to_standard, to_standard_lowercase,
 };
+pub use decoder::{Decoder, MorseDecoder};
 pub use encode_ascii::{encode_stream_ascii, encode_string_ascii};
-pub use encode_unicode::{encode_stream, encode_string};
+pub use encode_unicode::{
+    EncodeOptions, encode_stream, encode_stream_with, encode_stream_with_options, encode_string,
+    encode_string_with, encode_string_with_options,
+};
+pub use encoder::{Encoder, MorseEncoder};
+pub use format::MorseFormat;
+pub use iter::{DecodeIter, DecodedItem, EncodeIter, Symbol, decode_iter, encode_iter};
+pub use span::{Span, decode_spans, encode_spans};
+pub use timing::{KeyEventReader, KeyEvents, KeyState, key_events, key_events_to_morse};
+pub use timing_decode::{TimingDecoder, decode_timings, timings_to_morse};
+pub use variant::Variant;
+pub use verify::{Loss, LossyChar, verify};
 
 #[test]
 fn test_unicode_round_trip() {
@@ -1,3 +1,5 @@
+use std::io::Write;
+
 use ripmors::*;
 
 use clap::{Parser, ValueEnum};
@@ -8,6 +10,12 @@ enum EncodeVariant {
     #[default]
     Unicode,
     Ascii,
+    Greek,
+    Russian,
+    Japanese,
+    Korean,
+    Hebrew,
+    Arabic,
 }
 
 #[derive(ValueEnum, Debug, Default, Clone, Copy, Eq, Parser, PartialEq)]
@@ -18,11 +26,28 @@ enum DecodeVariant {
     Greek,
     Russian,
     Japanese,
+    JapaneseHiragana,
     Korean,
     Hebrew,
     Arabic,
 }
 
+#[derive(ValueEnum, Debug, Default, Clone, Copy, Eq, Parser, PartialEq)]
+#[clap(rename_all = "kebab_case")]
+enum Case {
+    #[default]
+    Upper,
+    Lower,
+}
+
+#[derive(ValueEnum, Debug, Default, Clone, Copy, Eq, Parser, PartialEq)]
+#[clap(rename_all = "kebab_case")]
+enum ErrorSignalVariant {
+    #[default]
+    Ignore,
+    DeletePreviousWord,
+}
+
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
 struct Args {
@@ -30,28 +55,234 @@ struct Args {
     decode: Option<DecodeVariant>,
     #[arg(short, long, num_args = 0..=1, default_missing_value = "unicode")]
     encode: Option<EncodeVariant>,
+    /// Fail on unknown Morse sequences instead of ignoring them
+    #[arg(long, requires = "decode")]
+    strict: bool,
+    /// Character to output in place of unknown Morse sequences
+    #[arg(long, requires = "decode", conflicts_with = "strict")]
+    replacement: Option<char>,
+    /// What to do when receiving the error signal (eight dots)
+    #[arg(
+        long,
+        requires = "decode",
+        conflicts_with = "strict",
+        default_value = "ignore"
+    )]
+    error_signal: ErrorSignalVariant,
+    /// Decode prosigns in angle-bracket notation, such as <SK>
+    #[arg(long, requires = "decode", conflicts_with = "strict")]
+    prosigns: bool,
+    /// Also accept _, –, — and − as dashes, and · and • as dots
+    #[arg(long, requires = "decode", conflicts_with = "strict")]
+    normalize_symbols: bool,
+    /// Also accept | as a word space
+    #[arg(long, requires = "decode", conflicts_with = "strict")]
+    bar_word_gap: bool,
+    /// Accept three spaces or more as a word space
+    #[arg(long, requires = "decode", conflicts_with = "strict")]
+    spaces_word_gap: bool,
+    /// Encode consecutive spaces as a single word space, not supported by the ascii encoder
+    #[arg(long, conflicts_with_all = ["decode", "table"])]
+    collapse_spaces: bool,
+    /// Combine kana followed by a dakuten (゛) or handakuten (゜), such as コ゛ into ゴ
+    #[arg(long, requires = "decode", conflicts_with = "strict")]
+    compose_kana: bool,
+    /// Case of the decoded Latin, Greek and Cyrillic letters
+    #[arg(
+        long,
+        requires = "decode",
+        conflicts_with = "table",
+        default_value = "upper"
+    )]
+    case: Case,
+    /// Use a custom code table, with a Morse sequence and its text on each line
+    #[arg(long, conflicts_with = "encode")]
+    table: Option<std::path::PathBuf>,
+    /// Report the characters of a file that do not survive encoding then decoding
+    #[arg(long, conflicts_with_all = ["decode", "table"])]
+    check: Option<std::path::PathBuf>,
+    /// Write the encoded Morse code as audio to a WAV file, or decode it from a WAV file
+    #[arg(long)]
+    wav: Option<std::path::PathBuf>,
+    /// Speed of the audio, in words per minute
+    #[arg(long, requires = "wav", default_value_t = 20.0)]
+    wpm: f64,
+    /// Stretch letter and word spaces to this overall speed, in words per minute
+    #[arg(long, requires = "wav")]
+    farnsworth: Option<f64>,
+    /// Stretch word spaces to this overall speed, in words per minute
+    #[arg(long, requires = "wav", conflicts_with = "farnsworth")]
+    wordsworth: Option<f64>,
+    /// Frequency of the audio tone, in hertz
+    #[arg(long, requires = "wav", default_value_t = 600.0)]
+    frequency: f64,
+}
+
+fn exit_with_error(error: impl std::fmt::Display) -> ! {
+    eprintln!("ripmors: {error}");
+    std::process::exit(1);
+}
+
+fn decode(args: &Args, alphabet: impl MorseAlphabet) {
+    let mut input: Box<dyn std::io::Read> = match &args.wav {
+        Some(path) => {
+            let mut file = std::fs::File::open(path).unwrap_or_else(|e| exit_with_error(e));
+            let morse = wav_to_morse(&mut file).unwrap_or_else(|e| exit_with_error(e));
+            Box::new(std::io::Cursor::new(morse))
+        }
+        None => Box::new(std::io::stdin()),
+    };
+    let mut stdout = std::io::stdout();
+    if args.strict {
+        if let Err(e) = decode_stream_strict(&mut input, &mut stdout, alphabet) {
+            exit_with_error(e);
+        }
+    } else {
+        let options = DecodeOptions {
+            replacement: args.replacement,
+            error_signal: match args.error_signal {
+                ErrorSignalVariant::Ignore => ErrorSignal::Ignore,
+                ErrorSignalVariant::DeletePreviousWord => ErrorSignal::DeletePreviousWord,
+            },
+            prosigns: args.prosigns,
+            normalize_symbols: args.normalize_symbols,
+            bar_word_gap: args.bar_word_gap,
+            spaces_word_gap: args.spaces_word_gap,
+            compose_kana: args.compose_kana,
+            ..Default::default()
+        };
+        decode_stream_with(&mut input, &mut stdout, alphabet, &options).unwrap();
+    }
+}
+
+fn encode_options(args: &Args) -> EncodeOptions {
+    EncodeOptions {
+        variant: encode_variant(args.encode.unwrap_or_default()),
+        collapse_spaces: args.collapse_spaces,
+        ..Default::default()
+    }
+}
+
+fn encode_variant(variant: EncodeVariant) -> Variant {
+    match variant {
+        EncodeVariant::Unicode | EncodeVariant::Ascii => Variant::Standard,
+        EncodeVariant::Greek => Variant::Greek,
+        EncodeVariant::Russian => Variant::Russian,
+        EncodeVariant::Japanese => Variant::Japanese,
+        EncodeVariant::Korean => Variant::Korean,
+        EncodeVariant::Hebrew => Variant::Hebrew,
+        EncodeVariant::Arabic => Variant::Arabic,
+    }
+}
+
+fn check(path: &std::path::Path, variant: EncodeVariant) {
+    if variant == EncodeVariant::Ascii {
+        exit_with_error("--check does not support the ascii encoder");
+    }
+    let input = std::fs::read_to_string(path).unwrap_or_else(|e| exit_with_error(e));
+    let lossy = verify(&input, encode_variant(variant));
+    let mut stdout = std::io::stdout().lock();
+    for lossy_char in &lossy {
+        writeln!(stdout, "{}:{lossy_char}", path.display()).unwrap();
+    }
+    if !lossy.is_empty() {
+        exit_with_error(format!(
+            "{} characters do not survive encoding then decoding",
+            lossy.len()
+        ));
+    }
+}
+
+fn wav(args: &Args, path: &std::path::Path, table: Option<&CodeTable>) {
+    let mut morse = Vec::new();
+    let mut stdin = std::io::stdin();
+    let variant = args.encode.unwrap_or_default();
+    if let Some(table) = table {
+        let input = std::io::read_to_string(stdin).unwrap();
+        morse = table.encode_string(&input).into_bytes();
+    } else if variant == EncodeVariant::Ascii {
+        encode_stream_ascii(&mut stdin, &mut morse).unwrap();
+    } else {
+        encode_stream_with_options(&mut stdin, &mut morse, &encode_options(args)).unwrap();
+    }
+    let options = AudioOptions {
+        wpm: args.wpm,
+        timing: match (args.farnsworth, args.wordsworth) {
+            (Some(effective_wpm), _) => Timing::Farnsworth { effective_wpm },
+            (_, Some(effective_wpm)) => Timing::Wordsworth { effective_wpm },
+            (None, None) => Timing::Standard,
+        },
+        frequency: args.frequency,
+        ..Default::default()
+    };
+    let file = std::fs::File::create(path).unwrap_or_else(|e| exit_with_error(e));
+    let mut output = std::io::BufWriter::new(file);
+    if let Err(e) = morse_to_wav(&morse, &mut output, &options).and_then(|()| output.flush()) {
+        exit_with_error(e);
+    }
 }
 
 fn main() {
     let args = Args::parse();
+    if args.collapse_spaces && args.encode == Some(EncodeVariant::Ascii) {
+        exit_with_error("--collapse-spaces does not support the ascii encoder");
+    }
+
+    if let Some(path) = &args.check {
+        check(path, args.encode.unwrap_or_default());
+        return;
+    }
 
     let mut stdin = std::io::stdin();
     let mut stdout = std::io::stdout();
 
+    let table = args
+        .table
+        .as_ref()
+        .map(|path| CodeTable::load(path).unwrap_or_else(|e| exit_with_error(e)));
+
     if let Some(variant) = args.decode {
-        let char_decode = match variant {
-            DecodeVariant::Standard => to_standard,
-            DecodeVariant::Greek => to_greek,
-            DecodeVariant::Russian => to_russian,
-            DecodeVariant::Japanese => to_japanese,
-            DecodeVariant::Korean => to_korean,
-            DecodeVariant::Hebrew => to_hebrew,
-            DecodeVariant::Arabic => to_arabic,
+        if let Some(table) = &table {
+            decode(&args, table);
+            return;
+        }
+        if args.case == Case::Lower {
+            let alphabet: fn(u8) -> &'static str = match variant {
+                DecodeVariant::Standard => to_standard_lowercase,
+                DecodeVariant::Greek => to_greek_lowercase,
+                DecodeVariant::Russian => to_russian_lowercase,
+                // other scripts have no case
+                _ => exit_with_error(
+                    "--case lower is only supported by the standard, greek and russian decoders",
+                ),
+            };
+            decode(&args, alphabet);
+            return;
+        }
+        let variant = match variant {
+            DecodeVariant::Standard => Variant::Standard,
+            DecodeVariant::Greek => Variant::Greek,
+            DecodeVariant::Russian => Variant::Russian,
+            DecodeVariant::Japanese => Variant::Japanese,
+            DecodeVariant::JapaneseHiragana => {
+                decode(&args, to_japanese_hiragana);
+                return;
+            }
+            DecodeVariant::Korean => Variant::Korean,
+            DecodeVariant::Hebrew => Variant::Hebrew,
+            DecodeVariant::Arabic => Variant::Arabic,
         };
-        decode_stream(&mut stdin, &mut stdout, char_decode).unwrap();
-    } else if args.encode == Some(EncodeVariant::Ascii) {
-        encode_stream_ascii(&mut stdin, &mut stdout).unwrap();
+        decode(&args, variant);
+    } else if let Some(path) = &args.wav {
+        wav(&args, path, table.as_ref());
+    } else if let Some(table) = &table {
+        table.encode_stream(&mut stdin, &mut stdout).unwrap();
     } else {
-        encode_stream(&mut stdin, &mut stdout).unwrap();
+        let variant = args.encode.unwrap_or_default();
+        if variant == EncodeVariant::Ascii {
+            encode_stream_ascii(&mut stdin, &mut stdout).unwrap();
+        } else {
+            encode_stream_with_options(&mut stdin, &mut stdout, &encode_options(&args)).unwrap();
+        }
     }
 }
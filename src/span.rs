@@ -0,0 +1,150 @@
+use std::ops::Range;
+
+use crate::decode::MorseAlphabet;
+use crate::encode_unicode::{Prosign, encode_buffer, parse_prosign};
+use crate::iter::decode_iter;
+use crate::variant::Variant;
+
+/// Correspondence between a part of the input and a part of the output, as returned by
+/// [encode_spans] and [decode_spans]
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Span {
+    /// Byte range in the input
+    pub input: Range<usize>,
+    /// Byte range in the output
+    pub output: Range<usize>,
+}
+
+/// Encode characters from a [string slice][&str] into a [String], along with the [Span] of each
+/// character.
+///
+/// The text is encoded like [encode_string_with][crate::encode_string_with] does, using the
+/// conventions of `variant`. There is one span for each character, or prosign, of `input`, in
+/// order. The output range of a character covers its Morse code, without the letter space that
+/// follows it; it is empty for characters that cannot be converted to Morse.
+///
+/// ```
+/// use ripmors::Variant;
+///
+/// let (morse, spans) = ripmors::encode_spans("Hé!", Variant::Standard);
+/// assert_eq!(morse, ".... ..-.. -.-.--");
+/// assert_eq!(spans[1].input, 1..3);
+/// assert_eq!(&morse[spans[1].output.clone()], "..-..");
+///
+/// // find the character being keyed
+/// let keyed = 12;
+/// let i = spans.partition_point(|span| span.output.end <= keyed);
+/// assert_eq!(&"Hé!"[spans[i].input.clone()], "!");
+/// ```
+pub fn encode_spans(input: &str, variant: Variant) -> (String, Vec<Span>) {
+    let mut output_buf = Vec::with_capacity(input.len() * 4);
+    let mut spans = Vec::new();
+    let mut start = 0;
+    while start < input.len() {
+        let len = if input.as_bytes()[start] == b'<'
+            && let Prosign::Found(len) = parse_prosign(&input.as_bytes()[start..])
+        {
+            len
+        } else {
+            input[start..].chars().next().unwrap().len_utf8()
+        };
+        let end = start + len;
+        let output_start = output_buf.len();
+        encode_buffer(&input[start..end], variant, true, &mut output_buf);
+        let output_start = match input.as_bytes()[start] {
+            // a tab, a line feed or a carriage return replaces the letter space before it
+            b'\t' | b'\n' | b'\r' => output_buf.len() - 1,
+            _ => output_start,
+        };
+        let output_end = match output_buf.last() {
+            Some(b' ') if output_buf.len() > output_start => output_buf.len() - 1,
+            _ => output_buf.len(),
+        };
+        spans.push(Span {
+            input: start..end,
+            output: output_start..output_end,
+        });
+        start = end;
+    }
+    output_buf.pop_if(|c| *c == b' ');
+    // SAFETY: encode_buffer only outputs ASCII, so it is valid UTF-8
+    let morse = unsafe { String::from_utf8_unchecked(output_buf) };
+    (morse, spans)
+}
+
+/// Decode Morse code from a [byte slice][slice] into a [String], along with the [Span] of each
+/// decoded item.
+///
+/// The Morse code is decoded like [decode_string][crate::decode_string] does, using `alphabet`.
+/// There is one span for each [item][crate::DecodedItem] returned by
+/// [decode_iter][crate::decode_iter], in order: sequences of elements, word spaces and
+/// whitespace. The output range is empty for sequences that do not correspond to any character.
+/// Like with [decode_string][crate::decode_string], a sequence directly followed by a slash is
+/// dropped, and has no span.
+///
+/// ```
+/// let (text, spans) = ripmors::decode_spans(b"-.. .- / -..", ripmors::to_russian);
+/// assert_eq!(text, "ДА Д");
+/// assert_eq!(spans[1].input, 4..6);
+/// assert_eq!(&text[spans[1].output.clone()], "А");
+/// ```
+pub fn decode_spans(input: &[u8], alphabet: impl MorseAlphabet) -> (String, Vec<Span>) {
+    let mut output = String::with_capacity(input.len());
+    let mut spans = Vec::new();
+    for item in decode_iter(input, alphabet) {
+        let output_start = output.len();
+        output.push_str(&item.text);
+        spans.push(Span {
+            input: item.input,
+            output: output_start..output.len(),
+        });
+    }
+    (output, spans)
+}
+
+#[test]
+fn test_encode_spans() {
+    use crate::encode_string_with;
+
+    let text = "Hello, wörld! <SK> <S でんしん\n\t<AR> ∞  ";
+    for variant in Variant::ALL {
+        let (morse, spans) = encode_spans(text, variant);
+        assert_eq!(morse, encode_string_with(text, variant));
+        // each character is encoded separately
+        let mut end = 0;
+        for span in &spans {
+            assert_eq!(span.input.start, end);
+            end = span.input.end;
+            let expected = encode_string_with(&text[span.input.clone()], variant);
+            assert_eq!(&morse[span.output.clone()], expected);
+        }
+        assert_eq!(end, text.len());
+    }
+
+    let (_, spans) = encode_spans("<SK>\n∞ ", Variant::Standard);
+    let f = |span: &Span| (span.input.clone(), span.output.clone());
+    assert_eq!(
+        spans.iter().map(f).collect::<Vec<_>>(),
+        [(0..4, 0..6), (4..5, 6..7), (5..8, 7..7), (8..9, 7..8)]
+    );
+}
+
+#[test]
+fn test_decode_spans() {
+    use crate::{decode_string, encode_string, to_standard};
+
+    let morse = encode_string("Hello, World! Привет, мир!\n\t<SK>");
+    for morse in [morse.as_bytes(), b".-/-", b"-- /.-/ / -.-./\n/.."] {
+        let (text, spans) = decode_spans(morse, to_standard);
+        assert_eq!(text, decode_string(morse, to_standard));
+        for span in &spans {
+            let expected = decode_string(&morse[span.input.clone()], to_standard);
+            assert_eq!(&text[span.output.clone()], expected);
+        }
+    }
+
+    // a sequence directly followed by a slash is dropped
+    let (text, spans) = decode_spans(b".-/-", to_standard);
+    assert_eq!(text, " T");
+    assert_eq!(spans[0].input, 2..3);
+}
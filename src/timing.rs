@@ -0,0 +1,276 @@
+use std::io::Read;
+use std::time::Duration;
+
+/// State of the key, in a [key event][key_events]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum KeyState {
+    /// Key pressed, transmitting a dot or a dash
+    Down,
+    /// Key released, between elements, letters or words
+    Up,
+    /// Key released for a word space written as a tab (`\t`), a line feed (`\n`) or a carriage
+    /// return (`\r`), which is kept so that the layout of the text survives
+    Whitespace(u8),
+}
+
+/// Iterator over the key events of some Morse code, see [key_events]
+#[derive(Clone, Debug)]
+pub struct KeyEvents<'a> {
+    morse: std::slice::Iter<'a, u8>,
+    /// Dot units of key-up since the last element
+    up: u32,
+    /// Whether the last byte was an element
+    in_letter: bool,
+    /// Event to return after the current one
+    pending: Option<(KeyState, u32)>,
+}
+
+impl<'a> KeyEvents<'a> {
+    /// Convert the durations of the events from dot units to a [Duration], given the duration of
+    /// a dot.
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use ripmors::KeyState::*;
+    ///
+    /// let ms = Duration::from_millis;
+    /// let events: Vec<_> = ripmors::key_events(b"-.").with_unit(ms(60)).collect();
+    /// assert_eq!(events, [(Down, ms(180)), (Up, ms(60)), (Down, ms(60))]);
+    /// ```
+    pub fn with_unit(self, unit: Duration) -> impl Iterator<Item = (KeyState, Duration)> + 'a {
+        self.map(move |(state, units)| (state, unit * units))
+    }
+}
+
+impl Iterator for KeyEvents<'_> {
+    type Item = (KeyState, u32);
+
+    fn next(&mut self) -> Option<(KeyState, u32)> {
+        if let Some(event) = self.pending.take() {
+            return Some(event);
+        }
+        for &c in self.morse.by_ref() {
+            let down = match c {
+                b'.' => 1,
+                b'-' => 3,
+                b' ' => {
+                    if self.in_letter {
+                        self.in_letter = false;
+                        self.up = 3;
+                    }
+                    continue;
+                }
+                b'/' => {
+                    // a letter space before a word space is part of it
+                    self.in_letter = false;
+                    self.up = self.up / 7 * 7 + 7;
+                    continue;
+                }
+                b'\t' | b'\n' | b'\r' => {
+                    let event = (KeyState::Whitespace(c), 7);
+                    let up = std::mem::take(&mut self.up) / 7 * 7;
+                    self.in_letter = false;
+                    if up == 0 {
+                        return Some(event);
+                    }
+                    self.pending = Some(event);
+                    return Some((KeyState::Up, up));
+                }
+                _ => continue,
+            };
+            let up = if self.in_letter { 1 } else { self.up };
+            self.in_letter = true;
+            self.up = 0;
+            if up == 0 {
+                return Some((KeyState::Down, down));
+            }
+            self.pending = Some((KeyState::Down, down));
+            return Some((KeyState::Up, up));
+        }
+        // trailing space
+        let up = std::mem::take(&mut self.up);
+        (up != 0).then_some((KeyState::Up, up))
+    }
+}
+
+/// Convert Morse code into key events, with their durations in dot units.
+///
+/// Bytes from `morse` are interpreted like in [decode_string][crate::decode_string]:
+///
+/// - Full stop (.) is a dot, with the key down for one unit;
+/// - Hyphen (-) is a dash, with the key down for three units;
+/// - Space ( ) is a letter space, with the key up for three units;
+/// - Slash (/) is a word space, with the key up for seven units;
+/// - Tab (\t), line feed (\n) and carriage return (\r) are word spaces, with a
+///   [Whitespace][KeyState::Whitespace] event each, so that [key_events_to_morse] keeps them.
+///
+/// Elements of a character are separated by the key up for one unit. A letter space next to a
+/// word space, such as in `.- / -...`, is part of it, and consecutive slashes are merged into a
+/// single key-up event, so that the key states alternate, except around tabs, line feeds and
+/// carriage returns. Other bytes are ignored.
+///
+/// ```
+/// use ripmors::KeyState::*;
+///
+/// let events: Vec<_> = ripmors::key_events(b".- / -").collect();
+/// assert_eq!(events, [(Down, 1), (Up, 1), (Down, 3), (Up, 7), (Down, 3)]);
+/// ```
+pub fn key_events(morse: &[u8]) -> KeyEvents<'_> {
+    KeyEvents {
+        morse: morse.iter(),
+        up: 0,
+        in_letter: false,
+        pending: None,
+    }
+}
+
+/// Append the Morse code for a key event lasting `units` dot units to `output`
+fn push_key_event(state: KeyState, units: u32, output: &mut Vec<u8>) {
+    match state {
+        KeyState::Down if units < 2 => output.push(b'.'),
+        KeyState::Down => output.push(b'-'),
+        KeyState::Up if units < 2 => (),
+        KeyState::Up if units < 5 => output.push(b' '),
+        KeyState::Up => {
+            for _ in 0..(units + 3) / 7 {
+                output.extend_from_slice(b" /");
+            }
+            output.push(b' ');
+        }
+        KeyState::Whitespace(c) => output.push(c),
+    }
+}
+
+/// Convert key events, with their durations in dot units, back into Morse code.
+///
+/// This is the reverse of [key_events]. Durations are rounded: a key down for more than one unit
+/// is a dash, a key up for two to four units is a letter space, and a key up for longer is one
+/// word space per seven units. [Whitespace][KeyState::Whitespace] events give back their tab,
+/// line feed or carriage return.
+///
+/// ```
+/// use ripmors::KeyState::*;
+///
+/// let events = [(Down, 1), (Up, 1), (Down, 3), (Up, 7), (Down, 3)];
+/// assert_eq!(ripmors::key_events_to_morse(events), ".- / -");
+/// ```
+pub fn key_events_to_morse(events: impl IntoIterator<Item = (KeyState, u32)>) -> String {
+    let mut output_buf = Vec::new();
+    for (state, units) in events {
+        push_key_event(state, units, &mut output_buf);
+    }
+    // SAFETY: push_key_event only outputs ASCII, so it is valid UTF-8
+    unsafe { String::from_utf8_unchecked(output_buf) }
+}
+
+/// [Read][std::io::Read] object producing Morse code from key events, see [key_events_to_morse].
+///
+/// This feeds key events into [decode_stream][crate::decode_stream] and similar functions.
+///
+/// ```
+/// use ripmors::{KeyEventReader, key_events, to_standard};
+///
+/// let events = key_events(b"-- --- .-. ... . / -.-. --- -.. .");
+/// let mut output = Vec::new();
+/// ripmors::decode_stream(&mut KeyEventReader::new(events), &mut output, to_standard).unwrap();
+/// assert_eq!(output, b"MORSE CODE");
+/// ```
+pub struct KeyEventReader<I> {
+    events: I,
+    buf: Vec<u8>,
+    pos: usize,
+}
+
+impl<I: Iterator<Item = (KeyState, u32)>> KeyEventReader<I> {
+    /// Create a reader producing the Morse code for `events`
+    pub fn new(events: impl IntoIterator<IntoIter = I>) -> Self {
+        KeyEventReader {
+            events: events.into_iter(),
+            buf: Vec::new(),
+            pos: 0,
+        }
+    }
+}
+
+impl<I: Iterator<Item = (KeyState, u32)>> Read for KeyEventReader<I> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, std::io::Error> {
+        if self.pos == self.buf.len() {
+            self.buf.clear();
+            self.pos = 0;
+            for (state, units) in self.events.by_ref() {
+                push_key_event(state, units, &mut self.buf);
+                if self.buf.len() >= buf.len() {
+                    break;
+                }
+            }
+        }
+        let available = &self.buf[self.pos..];
+        let n = available.len().min(buf.len());
+        buf[..n].copy_from_slice(&available[..n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+#[test]
+fn test_key_events() {
+    use KeyState::*;
+    let f = |morse: &[u8]| key_events(morse).collect::<Vec<_>>();
+    assert_eq!(f(b""), []);
+    assert_eq!(f(b".-"), [(Down, 1), (Up, 1), (Down, 3)]);
+    assert_eq!(f(b". -"), [(Down, 1), (Up, 3), (Down, 3)]);
+    assert_eq!(f(b". / -"), [(Down, 1), (Up, 7), (Down, 3)]);
+    assert_eq!(f(b". / / -"), [(Down, 1), (Up, 14), (Down, 3)]);
+    assert_eq!(
+        f(b".\n-\t"),
+        [
+            (Down, 1),
+            (Whitespace(b'\n'), 7),
+            (Down, 3),
+            (Whitespace(b'\t'), 7)
+        ]
+    );
+    assert_eq!(
+        f(b". / \r\n/ -"),
+        [
+            (Down, 1),
+            (Up, 7),
+            (Whitespace(b'\r'), 7),
+            (Whitespace(b'\n'), 7),
+            (Up, 7),
+            (Down, 3)
+        ]
+    );
+    assert_eq!(
+        f(b"/ .x. "),
+        [(Up, 7), (Down, 1), (Up, 1), (Down, 1), (Up, 3)]
+    );
+    let paris: u32 = key_events(b".--. .- .-. .. ... /")
+        .map(|(_, units)| units)
+        .sum();
+    assert_eq!(paris, 50);
+}
+
+#[test]
+fn test_key_events_round_trip() {
+    use crate::{decode_string, encode_string, to_standard};
+    for text in [
+        "PARIS",
+        "MORSE CODE",
+        "TWO  SPACES",
+        " SPACES AROUND ",
+        "ONE\nLINE\tTAB",
+    ] {
+        let morse = encode_string(text);
+        let round_trip = key_events_to_morse(key_events(morse.as_bytes()));
+        assert_eq!(decode_string(round_trip.as_bytes(), to_standard), text);
+    }
+
+    // large inputs go through several reads
+    let text = "THE QUICK BROWN FOX\nJUMPS OVER THE LAZY DOG ".repeat(1000);
+    let morse = encode_string(&text);
+    let mut reader = KeyEventReader::new(key_events(morse.as_bytes()));
+    let mut output = Vec::new();
+    crate::decode_stream(&mut reader, &mut output, to_standard).unwrap();
+    assert_eq!(output, text.as_bytes());
+}
@@ -0,0 +1,259 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use crate::decode::{MorseAlphabet, decode_string};
+
+/// Number of recent elements used by default to estimate the speed
+const DEFAULT_WINDOW: usize = 16;
+
+/// Convert durations of key-down and key-up into Morse code, following changes of speed.
+///
+/// Morse code keyed by hand rarely follows the standard timing: the speed drifts, and dashes are
+/// seldom exactly three times as long as dots. The decoder estimates the durations of dots and
+/// dashes from the most recent elements, and classifies each new element using the midpoint
+/// between the two. Spaces are then classified relative to the duration of a dot: up to 2 dots
+/// for a space between elements, up to 5 dots for a letter space, and more for a word space.
+///
+/// Until both dots and dashes have been seen, elements are assumed to be dots, unless the spaces
+/// between them are much shorter. In particular, a transmission starting with a lone dash, such
+/// as the letter T, might be misread.
+///
+/// ```
+/// use std::time::Duration;
+///
+/// let ms = Duration::from_millis;
+/// let mut decoder = ripmors::TimingDecoder::new();
+/// let mut morse = String::new();
+/// decoder.push(ms(55), ms(70), &mut morse);
+/// decoder.push(ms(200), ms(230), &mut morse);
+/// decoder.push(ms(210), ms(0), &mut morse);
+/// assert_eq!(morse, ".- -");
+/// ```
+#[derive(Clone, Debug)]
+pub struct TimingDecoder {
+    window: usize,
+    marks: VecDeque<f64>,
+    spaces: VecDeque<f64>,
+}
+
+impl Default for TimingDecoder {
+    fn default() -> Self {
+        TimingDecoder::new()
+    }
+}
+
+impl TimingDecoder {
+    /// Create a decoder estimating the speed from the 16 most recent elements
+    pub fn new() -> Self {
+        TimingDecoder::with_window(DEFAULT_WINDOW)
+    }
+
+    /// Create a decoder estimating the speed from the `window` most recent elements.
+    ///
+    /// A shorter window follows changes of speed faster, but is more sensitive to irregular
+    /// keying.
+    pub fn with_window(window: usize) -> Self {
+        let window = window.max(1);
+        TimingDecoder {
+            window,
+            marks: VecDeque::with_capacity(window),
+            spaces: VecDeque::with_capacity(window),
+        }
+    }
+
+    /// Estimate the durations of a dot and of a dash, in seconds, from at least one element
+    fn estimate(&self) -> (f64, f64) {
+        let mean = |values: &mut dyn Iterator<Item = f64>| {
+            let (sum, count) = values.fold((0.0, 0), |(sum, count), v| (sum + v, count + 1));
+            (count != 0).then(|| sum / f64::from(count))
+        };
+        let shortest = self.marks.iter().copied().fold(f64::INFINITY, f64::min);
+        let longest = self.marks.iter().copied().fold(0.0, f64::max);
+        // both dots and dashes: split them with the 2-means algorithm
+        let two_means = || {
+            let (mut dot, mut dash) = (shortest, longest);
+            for _ in 0..8 {
+                let threshold = (dot + dash) / 2.0;
+                dot = mean(&mut self.marks.iter().copied().filter(|&d| d < threshold))?;
+                dash = mean(&mut self.marks.iter().copied().filter(|&d| d >= threshold))?;
+            }
+            Some((dot, dash))
+        };
+        if longest > 2.0 * shortest
+            && let Some(estimate) = two_means()
+        {
+            return estimate;
+        }
+        let average = mean(&mut self.marks.iter().copied()).unwrap_or(0.0);
+        if self.spaces.iter().any(|&space| 2.0 * space <= shortest) {
+            // only dashes, since the spaces between elements are shorter
+            (average / 3.0, average)
+        } else {
+            // only dots
+            (average, 3.0 * average)
+        }
+    }
+
+    /// Current estimate of the duration of a dot
+    pub fn dot(&self) -> Option<Duration> {
+        if self.marks.is_empty() {
+            return None;
+        }
+        Some(Duration::from_secs_f64(self.estimate().0))
+    }
+
+    /// Classify an element lasting `key_down`, followed by a space lasting `key_up`, and append
+    /// them to `morse`.
+    ///
+    /// The output uses the same representation as [encode_string][crate::encode_string]: `.`
+    /// for a dot, `-` for a dash, a space for a letter space, and ` / ` for a word space.
+    pub fn push(&mut self, key_down: Duration, key_up: Duration, morse: &mut String) {
+        let (key_down, key_up) = (key_down.as_secs_f64(), key_up.as_secs_f64());
+        if self.marks.len() == self.window {
+            self.marks.pop_front();
+            self.spaces.pop_front();
+        }
+        self.marks.push_back(key_down);
+        self.spaces.push_back(key_up);
+        let (dot, dash) = self.estimate();
+
+        morse.push(if key_down < (dot + dash) / 2.0 {
+            '.'
+        } else {
+            '-'
+        });
+        if key_up >= 5.0 * dot {
+            morse.push_str(" / ");
+        } else if key_up >= 2.0 * dot {
+            morse.push(' ');
+        }
+    }
+}
+
+/// Convert a sequence of (key-down, key-up) durations into Morse code, see [TimingDecoder]
+pub fn timings_to_morse(timings: impl IntoIterator<Item = (Duration, Duration)>) -> String {
+    let mut decoder = TimingDecoder::new();
+    let mut morse = String::new();
+    for (key_down, key_up) in timings {
+        decoder.push(key_down, key_up, &mut morse);
+    }
+    morse
+}
+
+/// Decode a sequence of (key-down, key-up) durations into a [String].
+///
+/// The Morse code is recovered with [timings_to_morse], then decoded with
+/// [decode_string][crate::decode_string], using `alphabet`.
+///
+/// ```
+/// use std::time::Duration;
+///
+/// let ms = Duration::from_millis;
+/// // "-- --- .-. ... ." sent hastily, with short dashes
+/// let timings = [
+///     (ms(150), ms(60)), (ms(160), ms(200)),
+///     (ms(140), ms(50)), (ms(150), ms(60)), (ms(170), ms(190)),
+///     (ms(60), ms(50)), (ms(140), ms(70)), (ms(50), ms(200)),
+///     (ms(60), ms(50)), (ms(40), ms(40)), (ms(50), ms(180)),
+///     (ms(60), ms(0)),
+/// ];
+/// assert_eq!(ripmors::decode_timings(timings, ripmors::to_standard), "MORSE");
+/// ```
+pub fn decode_timings(
+    timings: impl IntoIterator<Item = (Duration, Duration)>,
+    alphabet: impl MorseAlphabet,
+) -> String {
+    decode_string(timings_to_morse(timings).as_bytes(), alphabet)
+}
+
+#[cfg(test)]
+fn morse_to_timings(morse: &str, dot: f64, dash: f64) -> Vec<(Duration, Duration)> {
+    // (key-down, key-up) in seconds, with a space after each element
+    let mut timings = Vec::new();
+    for c in morse.chars() {
+        match c {
+            '.' => timings.push((dot, dot)),
+            '-' => timings.push((dash, dot)),
+            ' ' => {
+                let space = &mut timings.last_mut().unwrap().1;
+                *space = space.max(3.0 * dot);
+            }
+            '/' => timings.last_mut().unwrap().1 = 7.0 * dot,
+            _ => unreachable!(),
+        }
+    }
+    timings
+        .into_iter()
+        .map(|(down, up)| (Duration::from_secs_f64(down), Duration::from_secs_f64(up)))
+        .collect()
+}
+
+#[test]
+fn test_timing_decoder() {
+    use crate::{encode_string, to_standard};
+    let text = "PACK MY BOX WITH FIVE DOZEN LIQUOR JUGS";
+    let morse = encode_string(text);
+
+    // standard timing at 20 WPM
+    let timings = morse_to_timings(&morse, 0.06, 0.18);
+    assert_eq!(timings_to_morse(timings.clone()), morse);
+    assert_eq!(decode_timings(timings, to_standard), text);
+
+    // long dashes
+    let timings = morse_to_timings(&morse, 0.06, 0.3);
+    assert_eq!(decode_timings(timings, to_standard), text);
+
+    // speed slowing down from 24 WPM to 8 WPM
+    let twice = format!("{text} {text}");
+    let timings = morse_to_timings(&encode_string(&twice), 0.05, 0.15);
+    let n = timings.len() as f64;
+    let timings: Vec<_> = timings
+        .into_iter()
+        .enumerate()
+        .map(|(i, (down, up))| {
+            let slowdown = 1.0 + 2.0 * i as f64 / n;
+            (down.mul_f64(slowdown), up.mul_f64(slowdown))
+        })
+        .collect();
+    assert_eq!(decode_timings(timings, to_standard), twice);
+
+    // irregular keying, with up to 20 % of jitter
+    let mut state = 1u32;
+    let mut jitter = |d: Duration| {
+        state = state.wrapping_mul(1664525).wrapping_add(1013904223);
+        d.mul_f64(0.8 + 0.4 * f64::from(state >> 16) / 65536.0)
+    };
+    let timings: Vec<_> = morse_to_timings(&morse, 0.06, 0.18)
+        .into_iter()
+        .map(|(down, up)| (jitter(down), jitter(up)))
+        .collect();
+    assert_eq!(decode_timings(timings, to_standard), text);
+}
+
+#[test]
+fn test_timing_decoder_speed() {
+    let ms = Duration::from_millis;
+    let mut decoder = TimingDecoder::with_window(4);
+    let mut morse = String::new();
+    assert_eq!(decoder.dot(), None);
+    for _ in 0..4 {
+        decoder.push(ms(50), ms(50), &mut morse);
+        decoder.push(ms(150), ms(50), &mut morse);
+    }
+    assert_eq!(decoder.dot(), Some(ms(50)));
+    for _ in 0..4 {
+        decoder.push(ms(80), ms(80), &mut morse);
+        decoder.push(ms(240), ms(80), &mut morse);
+    }
+    assert_eq!(decoder.dot(), Some(ms(80)));
+    assert_eq!(morse, ".-.-.-.-.-.-.-.-");
+
+    // zero durations are meaningless, but valid
+    let mut decoder = TimingDecoder::new();
+    let mut morse = String::new();
+    decoder.push(Duration::ZERO, Duration::ZERO, &mut morse);
+    decoder.push(Duration::ZERO, ms(50), &mut morse);
+    decoder.push(ms(50), Duration::ZERO, &mut morse);
+    assert_eq!(decoder.dot(), Some(Duration::ZERO));
+    assert_eq!(morse, "- / - / - / ");
+}
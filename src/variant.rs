@@ -0,0 +1,112 @@
+use crate::decode::MorseAlphabet;
+use crate::decode_mapping::{
+    to_arabic, to_greek, to_hebrew, to_japanese, to_korean, to_russian, to_standard,
+};
+
+/// Local variant of Morse code, used to select the conventions of a script when encoding.
+///
+/// Most characters have a single encoding in Morse code, but some variants use their own
+/// conventions, mostly for punctuation. For instance, the full stop is encoded as `.-.-.-` in
+/// International Morse code, but as `......` in Russian Morse code. Encoding with
+/// [encode_string_with][crate::encode_string_with] and decoding with the same variant gives back
+/// the original text, as long as it only uses characters of the variant.
+///
+/// A variant is also a [MorseAlphabet], equivalent to the corresponding function, such as
+/// [to_russian][crate::to_russian].
+///
+/// ```
+/// use ripmors::Variant;
+///
+/// let morse = ripmors::encode_string_with("Привет.", Variant::Russian);
+/// assert_eq!(morse, ".--. .-. .. .-- . - ......");
+/// assert_eq!(ripmors::decode_string(morse.as_bytes(), Variant::Russian), "ПРИВЕТ.");
+/// ```
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum Variant {
+    /// [International Morse code and Latin extensions](https://en.wikipedia.org/wiki/Morse_code#Letters,_numbers,_punctuation,_prosigns_for_Morse_code_and_non-Latin_variants)
+    #[default]
+    Standard,
+    /// Greek
+    Greek,
+    /// [Russian](https://en.wikipedia.org/wiki/Russian_Morse_code) (Cyrillic)
+    Russian,
+    /// [Japanese](https://en.wikipedia.org/wiki/Wabun_code) (Katakana)
+    Japanese,
+    /// [Korean](https://en.wikipedia.org/wiki/SKATS) (Hangul)
+    Korean,
+    /// Hebrew
+    Hebrew,
+    /// Arabic
+    Arabic,
+}
+
+impl Variant {
+    /// All the variants
+    pub const ALL: [Variant; 7] = [
+        Variant::Standard,
+        Variant::Greek,
+        Variant::Russian,
+        Variant::Japanese,
+        Variant::Korean,
+        Variant::Hebrew,
+        Variant::Arabic,
+    ];
+
+    /// Return the encoding of `c` followed by a letter space, when it differs from the one of
+    /// International Morse code.
+    pub(crate) fn encode_override(self, c: char) -> Option<&'static [u8]> {
+        let elements: &[u8] = match (self, c) {
+            // Russian Morse code
+            // https://en.wikipedia.org/wiki/Russian_Morse_code
+            (Variant::Russian, '.') => b"...... ",
+            (Variant::Russian, ',') => b".-.-.- ",
+            (Variant::Russian, ';') => b"-.-.- ",
+            (Variant::Russian, '(' | ')') => b"-.--.- ",
+            (Variant::Russian, '—') => b"-....- ",
+            (Variant::Russian, '-') => b"-...- ",
+            (Variant::Russian, '!') => b"--..-- ",
+
+            // Wabun code, using Japanese punctuation
+            (Variant::Japanese, '(') => b"-.--.- ",
+            (Variant::Japanese, ')') => b".-..-. ",
+            (Variant::Japanese, ',') => b".-.-.- ",
+            (Variant::Japanese, '.') => b".-.-.. ",
+            _ => return None,
+        };
+        Some(elements)
+    }
+}
+
+impl MorseAlphabet for Variant {
+    fn decode(&self, elements: u8) -> &str {
+        match self {
+            Variant::Standard => to_standard(elements),
+            Variant::Greek => to_greek(elements),
+            Variant::Russian => to_russian(elements),
+            Variant::Japanese => to_japanese(elements),
+            Variant::Korean => to_korean(elements),
+            Variant::Hebrew => to_hebrew(elements),
+            Variant::Arabic => to_arabic(elements),
+        }
+    }
+}
+
+#[test]
+fn test_variant_round_trip() {
+    use crate::{decode_string, encode_string_with};
+    // every character decoded by a variant should be encoded back to the same sequence
+    for variant in Variant::ALL {
+        for binary in 2..=255 {
+            let text = variant.decode(binary);
+            if text.is_empty() {
+                continue;
+            }
+            let morse = encode_string_with(text, variant);
+            assert_eq!(
+                decode_string(morse.as_bytes(), variant),
+                text,
+                "{variant:?}: {text:?} encoded as {morse:?}",
+            );
+        }
+    }
+}
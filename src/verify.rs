@@ -0,0 +1,187 @@
+use std::collections::HashMap;
+
+use crate::decode::decode_string;
+use crate::encode_unicode::{Prosign, encode_string_with, parse_prosign};
+use crate::variant::Variant;
+
+/// How a character is altered when encoding then decoding it
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Loss {
+    /// The character has no Morse code, and disappears
+    Dropped,
+    /// The character comes back in another case, such as `a` as `A`
+    CaseFolded(String),
+    /// The character comes back as several characters, such as `ご` as `コ゛`
+    Decomposed(String),
+    /// The character comes back as another character, such as `あ` as `ア`
+    Replaced(String),
+}
+
+impl std::fmt::Display for Loss {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Loss::Dropped => write!(f, "dropped"),
+            Loss::CaseFolded(text) => write!(f, "case-folded to {text:?}"),
+            Loss::Decomposed(text) => write!(f, "decomposed into {text:?}"),
+            Loss::Replaced(text) => write!(f, "replaced by {text:?}"),
+        }
+    }
+}
+
+/// Character or prosign of the input that does not survive encoding then decoding, returned by
+/// [verify]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LossyChar {
+    /// Offset of the character in the input, in bytes
+    pub offset: usize,
+    /// Line of the character, starting at 1
+    pub line: usize,
+    /// Column of the character in its line, in characters, starting at 1
+    pub column: usize,
+    /// The character itself, or the whole prosign, such as `<SK>`
+    pub text: String,
+    /// What becomes of the character
+    pub loss: Loss,
+}
+
+impl std::fmt::Display for LossyChar {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}:{}: ", self.line, self.column)?;
+        let mut chars = self.text.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => write!(f, "{c:?} (U+{:04X})", c as u32)?,
+            _ => write!(f, "{:?}", self.text)?,
+        }
+        write!(f, " is {}", self.loss)
+    }
+}
+
+/// Return what becomes of `text`, a character or a prosign, when encoding it then decoding it
+/// with `variant`, if it changes.
+fn check_token(text: &str, variant: Variant) -> Option<Loss> {
+    let encoded = encode_string_with(text, variant);
+    let decoded = decode_string(encoded.as_bytes(), variant);
+    if decoded == text {
+        None
+    } else if decoded.is_empty() {
+        Some(Loss::Dropped)
+    } else if text.to_uppercase() == decoded || text.to_lowercase() == decoded {
+        Some(Loss::CaseFolded(decoded))
+    } else if decoded.chars().count() > text.chars().count() {
+        Some(Loss::Decomposed(decoded))
+    } else {
+        Some(Loss::Replaced(decoded))
+    }
+}
+
+/// List the characters from `input` that do not survive encoding then decoding with `variant`.
+///
+/// Morse code does not distinguish letter case, and many variants only support some of the
+/// characters of their script. This returns each character of the input that would be dropped,
+/// case-folded, decomposed, or replaced by another character after going through
+/// [encode_string_with] and [decode_string] with the same [Variant].
+///
+/// Characters are checked individually, except for prosigns, such as `<SK>`, which are checked as
+/// a whole, since [encode_string_with] encodes them as a single sequence.
+///
+/// ```
+/// use ripmors::{Loss, Variant};
+///
+/// let lossy = ripmors::verify("Morse € <SK>", Variant::Standard);
+/// assert_eq!(lossy.len(), 6);
+/// assert_eq!(lossy[0].text, "o");
+/// assert_eq!(lossy[0].loss, Loss::CaseFolded("O".to_string()));
+/// assert_eq!(lossy[4].text, "€");
+/// assert_eq!(lossy[4].loss, Loss::Dropped);
+/// assert_eq!(lossy[5].text, "<SK>");
+/// assert_eq!(lossy[5].loss, Loss::Dropped);
+/// ```
+pub fn verify(input: &str, variant: Variant) -> Vec<LossyChar> {
+    let mut cache = HashMap::new();
+    let mut lossy = Vec::new();
+    let mut line = 1;
+    let mut column = 1;
+    let mut offset = 0;
+    while offset < input.len() {
+        // group prosigns like encode_spans does
+        let len = if input.as_bytes()[offset] == b'<'
+            && let Prosign::Found(len) = parse_prosign(&input.as_bytes()[offset..])
+        {
+            len
+        } else {
+            input[offset..].chars().next().unwrap().len_utf8()
+        };
+        let text = &input[offset..offset + len];
+        let loss = cache
+            .entry(text)
+            .or_insert_with(|| check_token(text, variant));
+        if let Some(loss) = loss {
+            lossy.push(LossyChar {
+                offset,
+                line,
+                column,
+                text: text.to_string(),
+                loss: loss.clone(),
+            });
+        }
+        if text == "\n" {
+            line += 1;
+            column = 1;
+        } else {
+            column += text.chars().count();
+        }
+        offset += len;
+    }
+    lossy
+}
+
+#[test]
+fn test_verify() {
+    let losses = |input, variant| -> Vec<(String, Loss)> {
+        verify(input, variant)
+            .into_iter()
+            .map(|lossy| (lossy.text, lossy.loss))
+            .collect()
+    };
+    assert_eq!(losses("PARIS, 1234!\n\tOK", Variant::Standard), []);
+    assert_eq!(
+        losses("Ab€", Variant::Standard),
+        [
+            ("b".into(), Loss::CaseFolded("B".into())),
+            ("€".into(), Loss::Dropped)
+        ],
+    );
+    assert_eq!(
+        losses("モールスふごう", Variant::Japanese),
+        [
+            ("ふ".into(), Loss::Replaced("フ".into())),
+            ("ご".into(), Loss::Decomposed("コ゛".into())),
+            ("う".into(), Loss::Replaced("ウ".into())),
+        ],
+    );
+    assert_eq!(losses("Привет.", Variant::Russian)[0].0, "р");
+    assert_eq!(losses("ПРИВЕТ.", Variant::Russian), []);
+
+    let lossy = verify("A\nbc", Variant::Standard);
+    assert_eq!(lossy.len(), 2);
+    assert_eq!((lossy[1].offset, lossy[1].line, lossy[1].column), (3, 2, 2));
+    assert_eq!(
+        lossy[1].to_string(),
+        "2:2: 'c' (U+0063) is case-folded to \"C\""
+    );
+
+    // prosigns are checked as a whole
+    let lossy = verify("a <SK> <x>", Variant::Standard);
+    let lossy: Vec<_> = lossy.iter().map(ToString::to_string).collect();
+    assert_eq!(
+        lossy,
+        [
+            "1:1: 'a' (U+0061) is case-folded to \"A\"",
+            "1:3: \"<SK>\" is dropped",
+            // not a prosign
+            "1:8: '<' (U+003C) is replaced by \"(\"",
+            "1:9: 'x' (U+0078) is case-folded to \"X\"",
+            "1:10: '>' (U+003E) is replaced by \")\"",
+        ],
+    );
+}